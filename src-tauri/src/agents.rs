@@ -0,0 +1,71 @@
+//! Subagent discovery under `.claude/agents/`.
+//!
+//! Each `*.md` file is one agent: YAML frontmatter for its settings and a
+//! body that becomes its system prompt. Project, user and plugin agents are
+//! merged as described in `scopes`; Claude Code tells agents apart by their
+//! frontmatter `name`, so that is what shadowing compares.
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::frontmatter::{self, AgentFrontmatter, ExtraKeys, ParseError};
+use crate::scopes::{self, ConfigRoot, Origin};
+use crate::{current_project_dir, AppState};
+
+/// Agent metadata parsed from YAML frontmatter
+#[derive(Clone, Serialize)]
+pub struct AgentInfo {
+    /// File name without `.md`; prefixed with the plugin for plugin agents
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    pub model: String,
+    /// Allowed tools; `None` means the agent inherits all tools
+    pub tools: Option<Vec<String>>,
+    pub color: Option<String>,
+    /// Frontmatter keys without a dedicated field
+    pub extra: ExtraKeys,
+    /// Why the frontmatter couldn't be read, if it couldn't
+    pub error: Option<ParseError>,
+    #[serde(flatten)]
+    pub origin: Origin,
+}
+
+/// All agents in one root's `agents/` directory, sorted by slug
+pub(crate) fn scan(root: &ConfigRoot) -> Vec<AgentInfo> {
+    let mut agents = Vec::new();
+    let Ok(entries) = std::fs::read_dir(root.dir.join("agents")) else {
+        return agents;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(slug) = file_name.strip_suffix(".md") else {
+            continue;
+        };
+        let content = std::fs::read_to_string(entry.path()).unwrap_or_default();
+        let (fm, _, error) = frontmatter::parse::<AgentFrontmatter>(&content);
+
+        agents.push(AgentInfo {
+            name: root.qualify(fm.name.as_deref().unwrap_or(slug)),
+            description: fm.description.unwrap_or_default(),
+            model: fm.model.unwrap_or_else(|| "sonnet".to_string()),
+            tools: fm.tools,
+            color: fm.color,
+            extra: fm.extra,
+            error,
+            slug: root.qualify(slug),
+            origin: root.origin(&entry.path()),
+        });
+    }
+    agents.sort_by(|a, b| a.slug.cmp(&b.slug));
+    agents
+}
+
+/// Discover project, user and plugin agents with metadata
+#[tauri::command]
+pub fn discover_agents(state: State<AppState>) -> Result<Vec<AgentInfo>, String> {
+    let dir = current_project_dir(&state)?;
+    let mut agents: Vec<AgentInfo> = scopes::config_roots(&dir).iter().flat_map(scan).collect();
+    scopes::mark_shadowed(&mut agents, |a| a.name.clone(), |a| &mut a.origin);
+    Ok(agents)
+}
@@ -0,0 +1,333 @@
+//! Per-run file change reports.
+//!
+//! The run directory is fingerprinted (size, mtime and content hash) before
+//! and after each run. Files whose fingerprint changed are reported in a
+//! `files-changed` event with line stats, and their unified diffs can be
+//! fetched afterwards. "Before" contents come from the run's checkpoint,
+//! "after" contents are captured when the run finishes.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use tauri::State;
+
+use crate::{checkpoint, files, walk, AppState};
+
+/// How many finished runs keep their report around for `get_file_diff`
+const MAX_REPORTS: usize = 20;
+/// Larger files are reported but not kept in memory for diffing
+const MAX_DIFF_BYTES: u64 = 1024 * 1024;
+
+/// Fingerprint of a single file
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct FileStamp {
+    size: u64,
+    mtime: u64,
+    hash: String,
+}
+
+/// Fingerprints of every non-ignored file, keyed by relative path
+pub(crate) type TreeSnapshot = HashMap<String, FileStamp>;
+
+/// One changed file in a run report
+#[derive(Clone, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    /// Line counts; both zero when either version couldn't be read as text
+    pub additions: usize,
+    pub deletions: usize,
+    pub binary: bool,
+    /// Either version is over the diff size limit
+    pub too_large: bool,
+}
+
+/// Payload of the `files-changed` event
+#[derive(Clone, Serialize)]
+pub struct FilesChangedEvent {
+    pub run_id: String,
+    pub added: Vec<FileChange>,
+    pub modified: Vec<FileChange>,
+    pub deleted: Vec<FileChange>,
+}
+
+/// Unified diff of one file changed by a run
+#[derive(Clone, Serialize)]
+pub struct FileDiff {
+    pub run_id: String,
+    pub path: String,
+    /// "added", "modified" or "deleted"
+    pub status: String,
+    pub binary: bool,
+    pub diff: String,
+}
+
+/// What a finished run changed, kept for later diff requests
+pub(crate) struct RunReport {
+    run_id: String,
+    root: String,
+    changes: FilesChangedEvent,
+    /// Text content right after the run, per changed path
+    after: HashMap<String, Option<String>>,
+}
+
+/// Reports of recent runs, newest last
+#[derive(Default)]
+pub struct RunReports {
+    reports: VecDeque<RunReport>,
+    /// Last snapshot taken, reused so unchanged files aren't re-hashed
+    last_snapshot: Option<(String, TreeSnapshot)>,
+}
+
+/// Fingerprint every non-ignored file under `root`. Hashes from `previous`
+/// are reused for files whose size and mtime didn't move.
+pub(crate) fn snapshot_tree(root: &Path, previous: Option<&TreeSnapshot>) -> TreeSnapshot {
+    let mut snapshot = TreeSnapshot::new();
+    for entry in walk::walker(root).build().flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let path = walk::relative_path(root, entry.path());
+        let size = meta.len();
+        let mtime = files::mtime_millis(&meta);
+
+        let reused = previous
+            .and_then(|prev| prev.get(&path))
+            .filter(|stamp| stamp.size == size && stamp.mtime == mtime)
+            .map(|stamp| stamp.hash.clone());
+        let Some(hash) = reused.or_else(|| files::hash_file(entry.path()).ok()) else {
+            continue;
+        };
+        snapshot.insert(path, FileStamp { size, mtime, hash });
+    }
+    snapshot
+}
+
+/// Decode file bytes as text, or `None` for binary content
+fn as_text(bytes: &[u8]) -> Option<String> {
+    if walk::looks_binary(bytes) {
+        return None;
+    }
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+fn line_stats(before: &str, after: &str) -> (usize, usize) {
+    let diff = similar::TextDiff::from_lines(before, after);
+    let mut additions = 0;
+    let mut deletions = 0;
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            similar::ChangeTag::Insert => additions += 1,
+            similar::ChangeTag::Delete => deletions += 1,
+            similar::ChangeTag::Equal => {}
+        }
+    }
+    (additions, deletions)
+}
+
+/// Text of `path` at the run's checkpoint. Outer `None` = unknown/binary.
+fn before_text(root: &Path, run_id: &str, path: &str) -> Option<String> {
+    checkpoint::read_file_at(root, run_id, path)
+        .ok()
+        .flatten()
+        .and_then(|bytes| as_text(&bytes))
+}
+
+/// Compare two snapshots and build the run's report
+pub(crate) fn build_report(
+    root: &Path,
+    run_id: &str,
+    before: &TreeSnapshot,
+    after: &TreeSnapshot,
+) -> RunReport {
+    let mut changes = FilesChangedEvent {
+        run_id: run_id.to_string(),
+        added: Vec::new(),
+        modified: Vec::new(),
+        deleted: Vec::new(),
+    };
+    let mut after_text = HashMap::new();
+
+    let mut paths: Vec<&String> = before.keys().chain(after.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    for path in paths {
+        let old = before.get(path);
+        let new = after.get(path);
+        if let (Some(old), Some(new)) = (old, new) {
+            if old.hash == new.hash {
+                continue;
+            }
+        }
+
+        let too_large = old.is_some_and(|stamp| stamp.size > MAX_DIFF_BYTES)
+            || new.is_some_and(|stamp| stamp.size > MAX_DIFF_BYTES);
+        let new_bytes = new
+            .filter(|_| !too_large)
+            .and_then(|_| std::fs::read(root.join(path)).ok());
+        let old_bytes = old
+            .filter(|_| !too_large)
+            .and_then(|_| checkpoint::read_file_at(root, run_id, path).ok().flatten());
+        let binary = new_bytes.as_deref().is_some_and(walk::looks_binary)
+            || old_bytes.as_deref().is_some_and(walk::looks_binary);
+        let new_text = new_bytes.as_deref().and_then(as_text);
+        let old_text = old_bytes.as_deref().and_then(as_text);
+
+        // Counting against a version that couldn't be read (too large,
+        // binary, not UTF-8) would report the whole file as added or deleted
+        let readable =
+            (old.is_none() || old_text.is_some()) && (new.is_none() || new_text.is_some());
+        let (additions, deletions) = if readable {
+            line_stats(
+                old_text.as_deref().unwrap_or(""),
+                new_text.as_deref().unwrap_or(""),
+            )
+        } else {
+            (0, 0)
+        };
+        let change = FileChange {
+            path: path.clone(),
+            additions,
+            deletions,
+            binary,
+            too_large,
+        };
+
+        match (old, new) {
+            (None, Some(_)) => changes.added.push(change),
+            (Some(_), Some(_)) => changes.modified.push(change),
+            (Some(_), None) => changes.deleted.push(change),
+            (None, None) => continue,
+        }
+        after_text.insert(path.clone(), new_text);
+    }
+
+    RunReport {
+        run_id: run_id.to_string(),
+        root: root.to_string_lossy().to_string(),
+        changes,
+        after: after_text,
+    }
+}
+
+impl RunReports {
+    /// The last snapshot of `root`, so unchanged files needn't be re-hashed
+    pub(crate) fn previous_snapshot(&self, root: &str) -> Option<TreeSnapshot> {
+        self.last_snapshot
+            .as_ref()
+            .filter(|(key, _)| key == root)
+            .map(|(_, snapshot)| snapshot.clone())
+    }
+
+    /// Keep a finished run's report, dropping the oldest past the limit
+    pub(crate) fn push(&mut self, report: RunReport, after: TreeSnapshot) -> FilesChangedEvent {
+        let event = report.changes.clone();
+        self.last_snapshot = Some((report.root.clone(), after));
+        self.reports.push_back(report);
+        while self.reports.len() > MAX_REPORTS {
+            self.reports.pop_front();
+        }
+        event
+    }
+}
+
+/// Files changed by a finished run
+#[tauri::command]
+pub fn get_run_changes(
+    state: State<AppState>,
+    run_id: String,
+) -> Result<FilesChangedEvent, String> {
+    let reports = state.run_reports.lock().unwrap();
+    reports
+        .reports
+        .iter()
+        .find(|r| r.run_id == run_id)
+        .map(|r| r.changes.clone())
+        .ok_or_else(|| format!("No change report for run {}", run_id))
+}
+
+/// Unified diff of one file as changed by a run
+#[tauri::command]
+pub fn get_file_diff(
+    state: State<AppState>,
+    run_id: String,
+    path: String,
+) -> Result<FileDiff, String> {
+    let (root, status, binary, too_large, after) = {
+        let reports = state.run_reports.lock().unwrap();
+        let report = reports
+            .reports
+            .iter()
+            .find(|r| r.run_id == run_id)
+            .ok_or_else(|| format!("No change report for run {}", run_id))?;
+        let changes = &report.changes;
+        let (status, change) = [
+            ("added", &changes.added),
+            ("modified", &changes.modified),
+            ("deleted", &changes.deleted),
+        ]
+        .into_iter()
+        .find_map(|(status, list)| list.iter().find(|c| c.path == path).map(|c| (status, c)))
+        .ok_or_else(|| format!("{} was not changed by run {}", path, run_id))?;
+        (
+            report.root.clone(),
+            status,
+            change.binary,
+            change.too_large,
+            report.after.get(&path).cloned().flatten(),
+        )
+    };
+
+    if binary {
+        return Ok(FileDiff {
+            run_id,
+            path,
+            status: status.to_string(),
+            binary: true,
+            diff: String::new(),
+        });
+    }
+
+    if too_large {
+        return Err("The file is too large to diff".to_string());
+    }
+
+    let before = if status == "added" {
+        String::new()
+    } else {
+        before_text(Path::new(&root), &run_id, &path)
+            .ok_or("The file's previous version is not available")?
+    };
+    let after = if status == "deleted" {
+        String::new()
+    } else {
+        after.ok_or("The file is too large to diff")?
+    };
+
+    let old_header = if status == "added" {
+        "/dev/null".to_string()
+    } else {
+        format!("a/{}", path)
+    };
+    let new_header = if status == "deleted" {
+        "/dev/null".to_string()
+    } else {
+        format!("b/{}", path)
+    };
+    let diff = similar::TextDiff::from_lines(&before, &after)
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_header, &new_header)
+        .to_string();
+
+    Ok(FileDiff {
+        run_id,
+        path,
+        status: status.to_string(),
+        binary: false,
+        diff,
+    })
+}
@@ -0,0 +1,251 @@
+//! Automatic pre-run checkpoints.
+//!
+//! Before every run the run directory is snapshotted into a shadow git
+//! repository under the app data directory, so the user's own `.git` history
+//! is never touched. Each snapshot is kept under `refs/checkpoints/<run_id>`.
+//! The project's `.gitignore` rules apply to snapshots as well.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::State;
+
+use crate::{active_run_dir, app_data_dir, git, now_secs, project_key, AppState};
+
+const REF_PREFIX: &str = "refs/checkpoints/";
+
+/// A snapshot taken before a run
+#[derive(Clone, Serialize)]
+pub struct Checkpoint {
+    pub run_id: String,
+    pub commit: String,
+    pub created_at: u64,
+    /// First line of the prompt that triggered the run
+    pub label: String,
+}
+
+/// Changes made since a checkpoint was taken
+#[derive(Clone, Serialize)]
+pub struct CheckpointDiff {
+    pub run_id: String,
+    pub files: Vec<git::FileStat>,
+    pub patch: String,
+}
+
+/// Shadow repository for one run directory
+fn shadow_dir(work_tree: &Path) -> Result<PathBuf, String> {
+    Ok(app_data_dir()?
+        .join("checkpoints")
+        .join(format!("{}.git", project_key(&work_tree.to_string_lossy()))))
+}
+
+/// git command bound to the shadow repo with `work_tree` as its working tree
+fn shadow_git(shadow: &Path, work_tree: &Path) -> Command {
+    let mut cmd = git::command(work_tree);
+    cmd.env("GIT_DIR", shadow).env("GIT_WORK_TREE", work_tree);
+    cmd
+}
+
+/// `git -c` arguments that commit to the shadow repository as the app
+fn identity() -> [String; 4] {
+    [
+        "-c".to_string(),
+        format!("user.name={}", git::FALLBACK_NAME),
+        "-c".to_string(),
+        format!("user.email={}", git::FALLBACK_EMAIL),
+    ]
+}
+
+fn run_shadow(shadow: &Path, work_tree: &Path, args: &[&str]) -> Result<String, String> {
+    let stdout = git::output(shadow_git(shadow, work_tree).args(args))?;
+    Ok(String::from_utf8_lossy(&stdout).trim_end().to_string())
+}
+
+/// Open (initializing on first use) the shadow repo for `work_tree`
+fn open_shadow(work_tree: &Path) -> Result<PathBuf, String> {
+    let shadow = shadow_dir(work_tree)?;
+    if !shadow.join("HEAD").exists() {
+        std::fs::create_dir_all(&shadow).map_err(|e| e.to_string())?;
+        run_shadow(&shadow, work_tree, &["init", "--quiet"])?;
+        // Store file contents byte-for-byte so a restore is exact
+        run_shadow(&shadow, work_tree, &["config", "core.autocrlf", "false"])?;
+    }
+    Ok(shadow)
+}
+
+/// Stage the whole working tree (tracked, untracked, deletions) into the shadow index
+fn stage_all(shadow: &Path, work_tree: &Path) -> Result<(), String> {
+    run_shadow(shadow, work_tree, &["add", "--all"]).map(|_| ())
+}
+
+fn checkpoint_ref(run_id: &str) -> Result<String, String> {
+    if run_id.is_empty()
+        || !run_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid run id: {}", run_id));
+    }
+    Ok(format!("{}{}", REF_PREFIX, run_id))
+}
+
+/// Snapshot `work_tree` and label it with `run_id`
+pub(crate) fn snapshot(work_tree: &Path, run_id: &str, label: &str) -> Result<Checkpoint, String> {
+    let reference = checkpoint_ref(run_id)?;
+    let shadow = open_shadow(work_tree)?;
+    stage_all(&shadow, work_tree)?;
+
+    let label = label.lines().next().unwrap_or("").trim().to_string();
+    let message = format!("{}\n\n{}", run_id, label);
+    let mut cmd = shadow_git(&shadow, work_tree);
+    cmd.args(identity()).args([
+        "commit",
+        "--quiet",
+        "--allow-empty",
+        "--no-verify",
+        "-m",
+        &message,
+    ]);
+    git::output(&mut cmd)?;
+
+    let commit = run_shadow(&shadow, work_tree, &["rev-parse", "HEAD"])?;
+    run_shadow(&shadow, work_tree, &["update-ref", &reference, &commit])?;
+
+    Ok(Checkpoint {
+        run_id: run_id.to_string(),
+        commit,
+        created_at: now_secs(),
+        label,
+    })
+}
+
+/// Read a file's contents as of a checkpoint. `None` if it didn't exist then.
+pub(crate) fn read_file_at(
+    work_tree: &Path,
+    run_id: &str,
+    path: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let reference = checkpoint_ref(run_id)?;
+    let shadow = shadow_dir(work_tree)?;
+    if !shadow.join("HEAD").exists() {
+        return Err(format!("No checkpoint for run {}", run_id));
+    }
+    let spec = format!("{}:{}", reference, path);
+    let object_type = run_shadow(&shadow, work_tree, &["cat-file", "-t", &spec]);
+    match object_type.as_deref() {
+        Ok("blob") => {
+            git::output(shadow_git(&shadow, work_tree).args(["cat-file", "blob", &spec])).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// List checkpoints for the current run directory, newest first
+#[tauri::command]
+pub fn list_checkpoints(state: State<AppState>) -> Result<Vec<Checkpoint>, String> {
+    let dir = active_run_dir(&state)?;
+    let work_tree = Path::new(&dir);
+    let shadow = shadow_dir(work_tree)?;
+    if !shadow.join("HEAD").exists() {
+        return Ok(Vec::new());
+    }
+
+    let output = run_shadow(
+        &shadow,
+        work_tree,
+        &[
+            "for-each-ref",
+            "--sort=-creatordate",
+            "--format=%(refname:strip=2)%00%(objectname)%00%(creatordate:unix)%00%(contents:body)%00%00",
+            REF_PREFIX,
+        ],
+    )?;
+
+    let checkpoints = output
+        .split("\0\0")
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').split('\0');
+            let run_id = fields.next()?.to_string();
+            if run_id.is_empty() {
+                return None;
+            }
+            Some(Checkpoint {
+                run_id,
+                commit: fields.next()?.to_string(),
+                created_at: fields.next()?.parse().unwrap_or(0),
+                label: fields.next().unwrap_or("").trim().to_string(),
+            })
+        })
+        .collect();
+    Ok(checkpoints)
+}
+
+/// Show everything that changed in the run directory since a checkpoint
+#[tauri::command]
+pub fn diff_checkpoint(state: State<AppState>, run_id: String) -> Result<CheckpointDiff, String> {
+    let dir = active_run_dir(&state)?;
+    let work_tree = Path::new(&dir);
+    let reference = checkpoint_ref(&run_id)?;
+    let shadow = open_shadow(work_tree)?;
+    run_shadow(
+        &shadow,
+        work_tree,
+        &["rev-parse", "--verify", "--quiet", &reference],
+    )
+    .map_err(|_| format!("No checkpoint for run {}", run_id))?;
+
+    stage_all(&shadow, work_tree)?;
+    let numstat = run_shadow(
+        &shadow,
+        work_tree,
+        &["diff", "--cached", "--no-renames", "--numstat", &reference],
+    )?;
+    let patch = run_shadow(
+        &shadow,
+        work_tree,
+        &["diff", "--cached", "--no-renames", &reference],
+    )?;
+
+    Ok(CheckpointDiff {
+        run_id,
+        files: git::parse_numstat(&numstat),
+        patch,
+    })
+}
+
+/// Put the run directory back exactly as it was before a run.
+/// The current state is snapshotted first, so a restore can itself be undone.
+/// Returns the checkpoint holding the pre-restore state.
+#[tauri::command]
+pub fn restore_checkpoint(state: State<AppState>, run_id: String) -> Result<Checkpoint, String> {
+    if state.child_pid.lock().unwrap().is_some() {
+        return Err("Stop the running task before restoring a checkpoint".to_string());
+    }
+    let dir = active_run_dir(&state)?;
+    let work_tree = Path::new(&dir);
+    let reference = checkpoint_ref(&run_id)?;
+    let shadow = open_shadow(work_tree)?;
+    run_shadow(
+        &shadow,
+        work_tree,
+        &["rev-parse", "--verify", "--quiet", &reference],
+    )
+    .map_err(|_| format!("No checkpoint for run {}", run_id))?;
+
+    let backup_id = format!("restore-{}", now_secs());
+    let backup = snapshot(
+        work_tree,
+        &backup_id,
+        &format!("Before restoring {}", run_id),
+    )?;
+
+    // With everything staged, read-tree -u rewrites changed files and deletes
+    // ones that didn't exist at the checkpoint; ignored files are left alone.
+    run_shadow(
+        &shadow,
+        work_tree,
+        &["read-tree", "-u", "--reset", &reference],
+    )?;
+
+    Ok(backup)
+}
@@ -0,0 +1,536 @@
+//! Starter CLAUDE.md drafted from the project itself.
+//!
+//! `draft_claude_md` looks where a new contributor would: the build scripts,
+//! the test, lint and format tooling `tooling` detects, the top-level layout
+//! and the conventions they imply. It returns the draft with a diff against
+//! the existing `CLAUDE.md`. Saving goes through `memory::write_memory_file`
+//! with the returned hash, so a file changed in the meantime is never
+//! overwritten.
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::OnceLock;
+use tauri::State;
+
+use crate::files::hash_file;
+use crate::{current_project_dir, detectors, git, manifests, tooling, walk, AppState};
+
+/// Command kinds in the order they're listed
+const COMMAND_KINDS: &[(&str, &str)] = &[
+    ("dev", "Start the dev server"),
+    ("build", "Build"),
+    ("test", "Run the tests"),
+    ("lint", "Lint"),
+    ("format", "Format"),
+    ("typecheck", "Type-check"),
+];
+
+/// Subdirectories listed per top-level directory
+const MAX_CHILDREN: usize = 8;
+
+/// What common top-level directories usually hold
+const DIRECTORY_ROLES: &[(&str, &str)] = &[
+    (".github", "GitHub workflows and templates"),
+    ("__tests__", "Tests"),
+    ("app", "Application code"),
+    ("apps", "Applications in the workspace"),
+    ("assets", "Images, fonts and other assets"),
+    ("benches", "Benchmarks"),
+    ("bin", "Executables and entry points"),
+    ("cmd", "Command entry points"),
+    ("components", "UI components"),
+    ("config", "Configuration"),
+    ("crates", "Rust crates in the workspace"),
+    ("deploy", "Deployment configuration"),
+    ("docs", "Documentation"),
+    ("e2e", "End-to-end tests"),
+    ("examples", "Examples"),
+    ("infra", "Infrastructure as code"),
+    ("internal", "Private packages"),
+    ("lib", "Library code"),
+    ("migrations", "Database migrations"),
+    ("packages", "Packages in the workspace"),
+    ("pages", "Page routes"),
+    ("pkg", "Public packages"),
+    ("public", "Static files served as-is"),
+    ("scripts", "Development and build scripts"),
+    ("spec", "Tests"),
+    ("src", "Source code"),
+    ("src-tauri", "Tauri backend (Rust)"),
+    ("static", "Static files served as-is"),
+    ("test", "Tests"),
+    ("tests", "Tests"),
+    ("tools", "Developer tooling"),
+];
+
+/// Directories that are build output or dependencies, never architecture
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "out",
+    "coverage",
+    "vendor",
+    "venv",
+    ".venv",
+    "__pycache__",
+];
+
+/// A command to build, test or check the project
+#[derive(Clone, Serialize)]
+pub struct ProjectCommand {
+    /// "dev", "build", "test", "lint", "format" or "typecheck"
+    pub kind: String,
+    pub command: String,
+    /// File the command was found in
+    pub source: String,
+}
+
+/// A top-level directory
+#[derive(Clone, Serialize)]
+pub struct LayoutEntry {
+    pub path: String,
+    pub description: Option<String>,
+    /// Its subdirectories, up to a handful
+    pub children: Vec<String>,
+}
+
+/// A lint, format or type-check tool and where it's configured
+#[derive(Clone, Serialize)]
+pub struct LintTool {
+    pub name: String,
+    pub config: String,
+}
+
+/// What the draft is based on
+#[derive(Clone, Serialize)]
+pub struct ProjectProfile {
+    pub name: String,
+    pub description: Option<String>,
+    pub languages: Vec<String>,
+    pub framework: Option<String>,
+    pub package_manager: Option<String>,
+    pub commands: Vec<ProjectCommand>,
+    pub layout: Vec<LayoutEntry>,
+    pub lint_tools: Vec<LintTool>,
+    /// Coding rules implied by the config, as sentences
+    pub conventions: Vec<String>,
+}
+
+/// A drafted CLAUDE.md and how it differs from the current one
+#[derive(Clone, Serialize)]
+pub struct ClaudeMdDraft {
+    pub path: String,
+    pub exists: bool,
+    /// Hash of the current file, to pass as the expected hash when saving
+    pub hash: Option<String>,
+    pub content: String,
+    /// Unified diff from the current file (or nothing) to the draft
+    pub diff: String,
+    pub profile: ProjectProfile,
+}
+
+fn read(root: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(root.join(name)).ok()
+}
+
+/// Command kind of a script or make target name, e.g. `test:e2e` is a test
+fn command_kind(name: &str) -> Option<&'static str> {
+    let base = name.split([':', '-']).next().unwrap_or(name);
+    match (name, base) {
+        ("typecheck" | "type-check" | "check-types" | "tsc", _) => Some("typecheck"),
+        (_, "dev" | "start" | "serve") => Some("dev"),
+        (_, "build") => Some("build"),
+        (_, "test") => Some("test"),
+        (_, "lint" | "check") => Some("lint"),
+        (_, "format" | "fmt") => Some("format"),
+        _ => None,
+    }
+}
+
+fn command(kind: &str, command: String, source: &str) -> ProjectCommand {
+    ProjectCommand {
+        kind: kind.to_string(),
+        command,
+        source: source.to_string(),
+    }
+}
+
+/// package.json: scripts, description and module type
+fn node_commands(root: &Path, package: &Value, commands: &mut Vec<ProjectCommand>) {
+    let manager = manifests::node_package_manager(root);
+    let scripts = package.get("scripts").and_then(Value::as_object);
+    for (name, _) in scripts.into_iter().flatten() {
+        if let Some(kind) = command_kind(name) {
+            commands.push(command(
+                kind,
+                manifests::run_script(manager, name),
+                "package.json",
+            ));
+        }
+    }
+}
+
+fn makefile_commands(makefile: &str, commands: &mut Vec<ProjectCommand>) {
+    static TARGET: OnceLock<Regex> = OnceLock::new();
+    let target =
+        TARGET.get_or_init(|| Regex::new(r"^([A-Za-z0-9][A-Za-z0-9_.-]*)\s*:([^=]|$)").unwrap());
+    let mut seen = Vec::new();
+    for line in makefile.lines() {
+        let Some(caps) = target.captures(line) else {
+            continue;
+        };
+        let name = caps[1].to_string();
+        if let Some(kind) = command_kind(&name) {
+            if !seen.contains(&name) {
+                commands.push(command(kind, format!("make {}", name), "Makefile"));
+                seen.push(name);
+            }
+        }
+    }
+}
+
+/// Make targets and scripts first, then build commands, then the checks
+/// `tooling` found; formatting is listed as the command that fixes it
+fn collect_commands(
+    root: &Path,
+    package: Option<&Value>,
+    tooling: &tooling::Tooling,
+) -> Vec<ProjectCommand> {
+    let mut commands = Vec::new();
+    if let Some(makefile) = read(root, "Makefile") {
+        makefile_commands(&makefile, &mut commands);
+    }
+    if let Some(package) = package {
+        node_commands(root, package, &mut commands);
+    }
+    if root.join("Cargo.toml").exists() {
+        commands.push(command("build", "cargo build".to_string(), "Cargo.toml"));
+    }
+    if root.join("go.mod").exists() {
+        commands.push(command("build", "go build ./...".to_string(), "go.mod"));
+    }
+    for tool in &tooling.tools {
+        let run = match (tool.kind.as_str(), &tool.fix_command) {
+            ("format", Some(fix)) => fix,
+            _ => &tool.command,
+        };
+        if commands.iter().all(|c| c.command != *run) {
+            commands.push(command(&tool.kind, run.clone(), &tool.source));
+        }
+    }
+    commands
+}
+
+fn layout(root: &Path) -> Vec<LayoutEntry> {
+    let subdirs = |dir: &Path| -> Vec<String> {
+        let mut names: Vec<String> = walk::walker(dir)
+            .max_depth(Some(1))
+            .build()
+            .flatten()
+            .filter(|entry| entry.depth() == 1 && entry.file_type().is_some_and(|t| t.is_dir()))
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| !SKIPPED_DIRS.contains(&name.as_str()))
+            .collect();
+        names.sort();
+        names
+    };
+    subdirs(root)
+        .into_iter()
+        .filter(|name| !name.starts_with('.') || name == ".github")
+        .map(|name| {
+            let mut children: Vec<String> = subdirs(&root.join(&name))
+                .into_iter()
+                .filter(|child| !child.starts_with('.'))
+                .collect();
+            children.truncate(MAX_CHILDREN);
+            LayoutEntry {
+                description: DIRECTORY_ROLES
+                    .iter()
+                    .find(|(dir, _)| *dir == name)
+                    .map(|(_, role)| role.to_string()),
+                path: name,
+                children,
+            }
+        })
+        .collect()
+}
+
+/// The linters, formatters and type checkers `tooling` found, plus
+/// EditorConfig and pre-commit
+fn lint_tools(root: &Path, tooling: &tooling::Tooling) -> Vec<LintTool> {
+    let mut tools: Vec<LintTool> = Vec::new();
+    let checks = tooling.tools.iter().filter(|t| t.kind != "test");
+    for tool in checks {
+        if tools.iter().all(|t| t.name != tool.name) {
+            tools.push(LintTool {
+                name: tool.name.clone(),
+                config: tool.source.clone(),
+            });
+        }
+    }
+    if root.join(".editorconfig").exists() {
+        tools.push(LintTool {
+            name: "EditorConfig".to_string(),
+            config: ".editorconfig".to_string(),
+        });
+    }
+    if let Some(pre_commit) = &tooling.pre_commit {
+        tools.push(LintTool {
+            name: "pre-commit".to_string(),
+            config: pre_commit.path.clone(),
+        });
+    }
+    tools
+}
+
+/// Indentation from the `[*]` section of .editorconfig
+fn editorconfig_indent(content: &str) -> Option<String> {
+    let mut in_all = false;
+    let mut style = None;
+    let mut size = None;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_all = line == "[*]";
+            continue;
+        }
+        if !in_all {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "indent_style" => style = Some(value.trim().to_string()),
+                "indent_size" => size = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+    match (style.as_deref(), size) {
+        (Some("tab"), _) => Some("Indent with tabs".to_string()),
+        (_, Some(size)) => Some(format!("Indent with {} spaces", size)),
+        _ => None,
+    }
+}
+
+/// Whether most recent commit subjects follow Conventional Commits
+fn uses_conventional_commits(root: &Path) -> bool {
+    let Ok(log) = git::run(root, &["log", "-n", "30", "--format=%s"]) else {
+        return false;
+    };
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(r"^(feat|fix|docs|style|refactor|perf|test|build|ci|chore|revert)(\(.+\))?!?: ")
+            .unwrap()
+    });
+    let subjects: Vec<&str> = log.lines().collect();
+    let matching = subjects.iter().filter(|s| pattern.is_match(s)).count();
+    subjects.len() >= 5 && matching * 10 >= subjects.len() * 6
+}
+
+fn conventions(root: &Path, package: Option<&Value>, tools: &[LintTool]) -> Vec<String> {
+    let mut rules = Vec::new();
+    if let Some(indent) = read(root, ".editorconfig").and_then(|c| editorconfig_indent(&c)) {
+        rules.push(format!("{} (from .editorconfig)", indent));
+    }
+    if let Some(prettier) = read(root, ".prettierrc")
+        .or_else(|| read(root, ".prettierrc.json"))
+        .and_then(|c| serde_json::from_str::<Value>(&c).ok())
+    {
+        if prettier.get("semi") == Some(&Value::Bool(false)) {
+            rules.push("Omit semicolons (Prettier `semi: false`)".to_string());
+        }
+        if prettier.get("singleQuote") == Some(&Value::Bool(true)) {
+            rules.push("Use single quotes in JavaScript/TypeScript".to_string());
+        }
+    }
+    // tsconfig.json allows comments, so look for the flag rather than parse
+    static STRICT: OnceLock<Regex> = OnceLock::new();
+    let strict = STRICT.get_or_init(|| Regex::new(r#""strict"\s*:\s*true"#).unwrap());
+    if read(root, "tsconfig.json").is_some_and(|c| strict.is_match(&c)) {
+        rules.push(
+            "TypeScript strict mode is on; fix type errors instead of silencing them with `any`"
+                .to_string(),
+        );
+    }
+    if package.and_then(|p| p.get("type")).and_then(Value::as_str) == Some("module") {
+        rules.push("The package is ES modules (`\"type\": \"module\"`); use `import`/`export`, not `require`".to_string());
+    }
+    let formatters: Vec<&str> = tools
+        .iter()
+        .map(|t| t.name.as_str())
+        .filter(|name| ["Prettier", "Biome", "rustfmt", "Black", "Ruff"].contains(name))
+        .collect();
+    if !formatters.is_empty() {
+        rules.push(format!(
+            "Format code with {} before committing; don't reformat unrelated code",
+            formatters.join(" / ")
+        ));
+    }
+    if tools.iter().any(|t| t.name == "pre-commit") {
+        rules.push(
+            "pre-commit hooks run on commit; don't bypass them with `--no-verify`".to_string(),
+        );
+    }
+    if uses_conventional_commits(root) {
+        rules.push(
+            "Write commit messages as Conventional Commits (`feat: …`, `fix: …`)".to_string(),
+        );
+    }
+    rules
+}
+
+/// Gather everything the draft is based on
+fn profile(root: &Path) -> ProjectProfile {
+    let package: Option<Value> =
+        read(root, "package.json").and_then(|c| serde_json::from_str(&c).ok());
+    let found = manifests::read_dir(root, root);
+    let extensions = detectors::ExtensionCounts::scan(root);
+    let stack = detectors::detect(root, root, &found, &extensions);
+    let tooling = tooling::detect(root, &found);
+    let lint_tools = lint_tools(root, &tooling);
+    let description = found
+        .iter()
+        .filter_map(|m| m.description.clone())
+        .find(|d| !d.trim().is_empty());
+
+    ProjectProfile {
+        name: root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        description,
+        languages: stack.language_names(),
+        framework: stack.frameworks.into_iter().next().map(|f| f.name),
+        package_manager: manifests::package_managers(root, &found).into_iter().next(),
+        commands: collect_commands(root, package.as_ref(), &tooling),
+        layout: layout(root),
+        conventions: conventions(root, package.as_ref(), &lint_tools),
+        lint_tools,
+    }
+}
+
+/// Markdown for a profile
+fn render(profile: &ProjectProfile) -> String {
+    let mut out = String::from(
+        "# CLAUDE.md\n\nThis file gives Claude Code guidance for working in this repository.\n\n",
+    );
+
+    out.push_str("## Project overview\n\n");
+    let stack = match (&profile.framework, profile.languages.is_empty()) {
+        (Some(framework), false) => format!(
+            "a {} project written in {}",
+            framework,
+            profile.languages.join(", ")
+        ),
+        (Some(framework), true) => format!("a {} project", framework),
+        (None, false) => format!("a {} project", profile.languages.join(", ")),
+        (None, true) => "a project".to_string(),
+    };
+    out.push_str(&format!("{} is {}.", profile.name, stack));
+    if let Some(description) = &profile.description {
+        let description = description.trim();
+        out.push_str(&format!(" {}", description));
+        if !description.ends_with(['.', '!', '?']) {
+            out.push('.');
+        }
+    }
+    out.push_str("\n\n");
+
+    if !profile.commands.is_empty() {
+        out.push_str("## Commands\n\n```bash\n");
+        let width = profile
+            .commands
+            .iter()
+            .map(|c| c.command.len())
+            .max()
+            .unwrap_or(0);
+        for (kind, label) in COMMAND_KINDS {
+            for command in profile.commands.iter().filter(|c| c.kind == *kind) {
+                out.push_str(&format!(
+                    "{:width$}  # {}\n",
+                    command.command,
+                    label,
+                    width = width
+                ));
+            }
+        }
+        out.push_str("```\n\n");
+    }
+
+    if !profile.layout.is_empty() {
+        out.push_str("## Architecture\n\n");
+        for entry in &profile.layout {
+            out.push_str(&format!("- `{}/`", entry.path));
+            if let Some(description) = &entry.description {
+                out.push_str(&format!(": {}", description));
+            }
+            if !entry.children.is_empty() {
+                let children: Vec<String> =
+                    entry.children.iter().map(|c| format!("`{}/`", c)).collect();
+                out.push_str(&format!(" ({})", children.join(", ")));
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Coding rules\n\n");
+    for rule in &profile.conventions {
+        out.push_str(&format!("- {}\n", rule));
+    }
+    let check: Vec<&str> = ["lint", "typecheck", "test"]
+        .iter()
+        .filter_map(|kind| profile.commands.iter().find(|c| c.kind == *kind))
+        .map(|c| c.command.as_str())
+        .collect();
+    if !check.is_empty() {
+        let commands: Vec<String> = check.iter().map(|c| format!("`{}`", c)).collect();
+        out.push_str(&format!(
+            "- Before finishing a change, run {} and fix what they report\n",
+            commands.join(", ")
+        ));
+    }
+    out.push_str("- Follow the style of the surrounding code; keep changes focused on the task\n");
+    out
+}
+
+/// Draft a CLAUDE.md for the current project and diff it against the
+/// existing one. Nothing is written. Profiling walks the tree and runs git,
+/// so it happens off the main thread.
+#[tauri::command]
+pub async fn draft_claude_md(state: State<'_, AppState>) -> Result<ClaudeMdDraft, String> {
+    let dir = current_project_dir(&state)?;
+    let root = Path::new(&dir);
+    let path = root.join("CLAUDE.md");
+    let profile = {
+        let root = root.to_path_buf();
+        tokio::task::spawn_blocking(move || profile(&root))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    let content = render(&profile);
+
+    let existing = std::fs::read_to_string(&path).ok();
+    let old_header = if existing.is_some() {
+        "a/CLAUDE.md"
+    } else {
+        "/dev/null"
+    };
+    let diff = similar::TextDiff::from_lines(existing.as_deref().unwrap_or(""), &content)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_header, "b/CLAUDE.md")
+        .to_string();
+
+    Ok(ClaudeMdDraft {
+        path: path.to_string_lossy().to_string(),
+        exists: existing.is_some(),
+        hash: existing.as_ref().and_then(|_| hash_file(&path).ok()),
+        content,
+        diff,
+        profile,
+    })
+}
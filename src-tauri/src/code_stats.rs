@@ -0,0 +1,466 @@
+//! Codebase size: files and lines per language.
+//!
+//! Walks the project with the usual ignore rules and counts code, comment
+//! and blank lines per language, approximately: a line is a comment when it
+//! starts with the language's comment syntax or sits inside a block
+//! comment. Vendored and generated directories that aren't ignored are left
+//! out of the counts and reported on their own, and so are files too large
+//! to read line by line. Results are cached against a hash of every file's
+//! path, size and modification time, so asking again for an unchanged tree
+//! is cheap.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::files::{language_for, mtime_millis};
+use crate::{current_project_dir, walk, AppState};
+
+/// Files beyond this are left out
+const MAX_FILES: usize = 200_000;
+
+/// Files listed as the largest
+const LARGEST_FILES: usize = 20;
+
+/// Files larger than this aren't read; they're only listed by size
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Directory names holding third-party code
+const VENDORED_DIRS: &[&str] = &[
+    "vendor",
+    "vendors",
+    "third_party",
+    "third-party",
+    "thirdparty",
+    "node_modules",
+    "bower_components",
+    "Pods",
+    ".yarn",
+];
+
+/// Directory names holding build output or generated code
+const GENERATED_DIRS: &[&str] = &[
+    "dist",
+    "build",
+    "out",
+    "target",
+    "gen",
+    "generated",
+    "__generated__",
+    ".next",
+    ".nuxt",
+    ".svelte-kit",
+    "coverage",
+    "__pycache__",
+];
+
+/// Generated files that would skew the counts
+const GENERATED_FILES: &[&str] = &[
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lock",
+    "Cargo.lock",
+    "poetry.lock",
+    "uv.lock",
+    "pdm.lock",
+    "Pipfile.lock",
+    "composer.lock",
+    "Gemfile.lock",
+    "go.sum",
+];
+
+/// (language id, display name, line comment markers, block comment)
+type Syntax = (
+    &'static str,
+    &'static str,
+    &'static [&'static str],
+    Option<(&'static str, &'static str)>,
+);
+
+const C_BLOCK: Option<(&str, &str)> = Some(("/*", "*/"));
+const HTML_BLOCK: Option<(&str, &str)> = Some(("<!--", "-->"));
+
+/// Comment syntax per language id of `files::language_for`
+const SYNTAX: &[Syntax] = &[
+    ("rust", "Rust", &["//"], C_BLOCK),
+    ("typescript", "TypeScript", &["//"], C_BLOCK),
+    ("tsx", "TSX", &["//"], C_BLOCK),
+    ("javascript", "JavaScript", &["//"], C_BLOCK),
+    ("jsx", "JSX", &["//"], C_BLOCK),
+    ("python", "Python", &["#"], None),
+    ("go", "Go", &["//"], C_BLOCK),
+    ("java", "Java", &["//"], C_BLOCK),
+    ("kotlin", "Kotlin", &["//"], C_BLOCK),
+    ("scala", "Scala", &["//"], C_BLOCK),
+    ("ruby", "Ruby", &["#"], Some(("=begin", "=end"))),
+    ("php", "PHP", &["//", "#"], C_BLOCK),
+    ("swift", "Swift", &["//"], C_BLOCK),
+    ("c", "C", &["//"], C_BLOCK),
+    ("cpp", "C++", &["//"], C_BLOCK),
+    ("csharp", "C#", &["//"], C_BLOCK),
+    ("objectivec", "Objective-C", &["//"], C_BLOCK),
+    ("dart", "Dart", &["//"], C_BLOCK),
+    ("elixir", "Elixir", &["#"], None),
+    ("erlang", "Erlang", &["%"], None),
+    ("haskell", "Haskell", &["--"], Some(("{-", "-}"))),
+    ("lua", "Lua", &["--"], Some(("--[[", "]]"))),
+    ("r", "R", &["#"], None),
+    ("perl", "Perl", &["#"], None),
+    ("bash", "Shell", &["#"], None),
+    ("powershell", "PowerShell", &["#"], Some(("<#", "#>"))),
+    ("sql", "SQL", &["--"], C_BLOCK),
+    ("html", "HTML", &[], HTML_BLOCK),
+    ("css", "CSS", &[], C_BLOCK),
+    ("scss", "SCSS", &["//"], C_BLOCK),
+    ("sass", "Sass", &["//"], C_BLOCK),
+    ("less", "Less", &["//"], C_BLOCK),
+    ("vue", "Vue", &["//"], HTML_BLOCK),
+    ("svelte", "Svelte", &["//"], HTML_BLOCK),
+    ("json", "JSON", &[], None),
+    ("yaml", "YAML", &["#"], None),
+    ("toml", "TOML", &["#"], None),
+    ("xml", "XML", &[], HTML_BLOCK),
+    ("markdown", "Markdown", &[], HTML_BLOCK),
+    ("hcl", "HCL", &["#", "//"], C_BLOCK),
+    ("graphql", "GraphQL", &["#"], None),
+    ("protobuf", "Protocol Buffers", &["//"], C_BLOCK),
+    ("ini", "INI", &[";", "#"], None),
+    ("docker", "Dockerfile", &["#"], None),
+    ("makefile", "Makefile", &["#"], None),
+    ("cmake", "CMake", &["#"], None),
+];
+
+/// Totals for one language
+#[derive(Clone, Serialize)]
+pub struct LanguageStats {
+    /// Language id, as used by the editor's highlighter
+    pub language: String,
+    pub name: String,
+    pub files: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+    pub bytes: u64,
+}
+
+/// One of the largest files
+#[derive(Clone, Serialize)]
+pub struct FileSize {
+    pub path: String,
+    pub language: String,
+    pub lines: usize,
+    pub code: usize,
+    pub bytes: u64,
+    /// Over the size limit, so its lines weren't counted
+    pub too_large: bool,
+}
+
+/// A vendored or generated directory left out of the counts
+#[derive(Clone, Serialize)]
+pub struct ExcludedDir {
+    pub path: String,
+    /// "vendored" or "generated"
+    pub kind: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Size of the codebase
+#[derive(Clone, Serialize)]
+pub struct CodeStats {
+    /// Hash of every counted path with its size and modification time
+    pub tree_hash: String,
+    /// Served from the cache because nothing changed
+    pub cached: bool,
+    pub files: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+    pub bytes: u64,
+    /// Most lines of code first
+    pub languages: Vec<LanguageStats>,
+    /// Files in no known language, binaries included
+    pub other_files: usize,
+    /// Most bytes first
+    pub largest_files: Vec<FileSize>,
+    pub excluded_dirs: Vec<ExcludedDir>,
+    /// Lock files and other generated files left out
+    pub generated_files: Vec<String>,
+    /// The project has more than 200,000 files; the rest weren't counted
+    pub truncated: bool,
+}
+
+/// Last result, for `get_code_stats`
+pub struct StatsCache {
+    root: String,
+    stats: CodeStats,
+}
+
+struct WalkedFile {
+    relative: String,
+    path: PathBuf,
+    bytes: u64,
+    mtime: u64,
+}
+
+#[derive(Default)]
+struct LineCounts {
+    code: usize,
+    comments: usize,
+    blanks: usize,
+}
+
+/// Files under `root` honouring ignore rules, sorted by path. The walk
+/// itself is sorted, so the same files are kept when there are too many.
+fn list_files(root: &Path) -> (Vec<WalkedFile>, bool) {
+    let mut files: Vec<WalkedFile> = walk::walker(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .take(MAX_FILES + 1)
+        .map(|entry| {
+            let meta = entry.metadata().ok();
+            WalkedFile {
+                relative: walk::relative_path(root, entry.path()),
+                bytes: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+                mtime: meta.as_ref().map(mtime_millis).unwrap_or(0),
+                path: entry.into_path(),
+            }
+        })
+        .collect();
+    let truncated = files.len() > MAX_FILES;
+    files.truncate(MAX_FILES);
+    files.sort_by(|a, b| a.relative.cmp(&b.relative));
+    (files, truncated)
+}
+
+fn tree_hash(files: &[WalkedFile]) -> String {
+    let mut hasher = Sha256::new();
+    for file in files {
+        hasher.update(file.relative.as_bytes());
+        hasher.update([0]);
+        hasher.update(file.bytes.to_le_bytes());
+        hasher.update(file.mtime.to_le_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// The vendored or generated directory a file sits in, if any
+fn excluded_dir(relative: &str) -> Option<(String, &'static str)> {
+    let parts: Vec<&str> = relative.split('/').collect();
+    let dirs = &parts[..parts.len().saturating_sub(1)];
+    dirs.iter().enumerate().find_map(|(index, name)| {
+        let kind = if VENDORED_DIRS.contains(name) {
+            "vendored"
+        } else if GENERATED_DIRS.contains(name) {
+            "generated"
+        } else {
+            return None;
+        };
+        Some((dirs[..=index].join("/"), kind))
+    })
+}
+
+fn is_generated_file(relative: &str) -> bool {
+    let name = relative.rsplit('/').next().unwrap_or(relative);
+    GENERATED_FILES.contains(&name) || name.ends_with(".min.js") || name.ends_with(".min.css")
+}
+
+fn count_lines(text: &str, syntax: &Syntax) -> LineCounts {
+    let (_, _, line_markers, block) = syntax;
+    let mut counts = LineCounts::default();
+    let mut in_block = false;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            counts.blanks += 1;
+            continue;
+        }
+        if in_block {
+            counts.comments += 1;
+            if let Some((_, end)) = block {
+                in_block = !line.contains(end);
+            }
+            continue;
+        }
+        if let Some((start, end)) = block {
+            if let Some(rest) = line.strip_prefix(start) {
+                counts.comments += 1;
+                in_block = !rest.contains(end);
+                continue;
+            }
+        }
+        if line_markers.iter().any(|marker| line.starts_with(marker)) {
+            counts.comments += 1;
+        } else {
+            counts.code += 1;
+        }
+    }
+    counts
+}
+
+fn collect(files: Vec<WalkedFile>, tree_hash: String, truncated: bool) -> CodeStats {
+    let mut languages: HashMap<&'static str, LanguageStats> = HashMap::new();
+    let mut excluded: Vec<ExcludedDir> = Vec::new();
+    let mut generated_files = Vec::new();
+    let mut sizes: Vec<FileSize> = Vec::new();
+    let mut other_files = 0;
+
+    for file in files {
+        if let Some((path, kind)) = excluded_dir(&file.relative) {
+            match excluded.iter_mut().find(|d| d.path == path) {
+                Some(dir) => {
+                    dir.files += 1;
+                    dir.bytes += file.bytes;
+                }
+                None => excluded.push(ExcludedDir {
+                    path,
+                    kind: kind.to_string(),
+                    files: 1,
+                    bytes: file.bytes,
+                }),
+            }
+            continue;
+        }
+        if is_generated_file(&file.relative) {
+            generated_files.push(file.relative);
+            continue;
+        }
+        let syntax = language_for(&file.path)
+            .and_then(|id| SYNTAX.iter().find(|(syntax_id, ..)| *syntax_id == id));
+        if let Some(syntax) = syntax.filter(|_| file.bytes > MAX_FILE_BYTES) {
+            sizes.push(FileSize {
+                path: file.relative,
+                language: syntax.0.to_string(),
+                lines: 0,
+                code: 0,
+                bytes: file.bytes,
+                too_large: true,
+            });
+            continue;
+        }
+        let text = syntax
+            .and_then(|_| std::fs::read(&file.path).ok())
+            .filter(|bytes| !walk::looks_binary(bytes))
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+        let (Some(syntax), Some(text)) = (syntax, text) else {
+            other_files += 1;
+            continue;
+        };
+
+        let counts = count_lines(&text, syntax);
+        let stats = languages.entry(syntax.0).or_insert_with(|| LanguageStats {
+            language: syntax.0.to_string(),
+            name: syntax.1.to_string(),
+            files: 0,
+            code: 0,
+            comments: 0,
+            blanks: 0,
+            bytes: 0,
+        });
+        stats.files += 1;
+        stats.code += counts.code;
+        stats.comments += counts.comments;
+        stats.blanks += counts.blanks;
+        stats.bytes += file.bytes;
+        sizes.push(FileSize {
+            path: file.relative,
+            language: syntax.0.to_string(),
+            lines: counts.code + counts.comments + counts.blanks,
+            code: counts.code,
+            bytes: file.bytes,
+            too_large: false,
+        });
+    }
+
+    let mut languages: Vec<LanguageStats> = languages.into_values().collect();
+    languages.sort_by(|a, b| b.code.cmp(&a.code).then_with(|| a.name.cmp(&b.name)));
+    sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+    sizes.truncate(LARGEST_FILES);
+
+    CodeStats {
+        tree_hash,
+        cached: false,
+        files: languages.iter().map(|l| l.files).sum(),
+        code: languages.iter().map(|l| l.code).sum(),
+        comments: languages.iter().map(|l| l.comments).sum(),
+        blanks: languages.iter().map(|l| l.blanks).sum(),
+        bytes: languages.iter().map(|l| l.bytes).sum(),
+        languages,
+        other_files,
+        largest_files: sizes,
+        excluded_dirs: excluded,
+        generated_files,
+        truncated,
+    }
+}
+
+/// Count files and lines per language in the current project
+#[tauri::command]
+pub async fn get_code_stats(state: State<'_, AppState>) -> Result<CodeStats, String> {
+    let dir = current_project_dir(&state)?;
+    let previous = state
+        .code_stats
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|cache| cache.root == dir)
+        .map(|cache| cache.stats.clone());
+
+    let root = dir.clone();
+    let stats = tokio::task::spawn_blocking(move || {
+        let (files, truncated) = list_files(Path::new(&root));
+        let hash = tree_hash(&files);
+        match previous.filter(|stats| stats.tree_hash == hash) {
+            Some(stats) => CodeStats {
+                cached: true,
+                ..stats
+            },
+            None => collect(files, hash, truncated),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    *state.code_stats.lock().unwrap() = Some(StatsCache {
+        root: dir,
+        stats: stats.clone(),
+    });
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syntax(id: &str) -> &'static Syntax {
+        SYNTAX
+            .iter()
+            .find(|(syntax_id, ..)| *syntax_id == id)
+            .unwrap()
+    }
+
+    fn counts(text: &str, id: &str) -> (usize, usize, usize) {
+        let counts = count_lines(text, syntax(id));
+        (counts.code, counts.comments, counts.blanks)
+    }
+
+    #[test]
+    fn count_lines_splits_code_comments_and_blanks() {
+        let rust = "// line\n/* block\n   still */\nfn main() {\n\n    let x = 1; // trailing\n}\n";
+        assert_eq!(counts(rust, "rust"), (3, 3, 1));
+        let python = "#!/usr/bin/env python\nimport os\n\n\n# done\n";
+        assert_eq!(counts(python, "python"), (1, 2, 2));
+    }
+
+    #[test]
+    fn count_lines_handles_one_line_blocks_and_html() {
+        assert_eq!(counts("/* a */\nb {}\n", "css"), (1, 1, 0));
+        assert_eq!(counts("<!--\nx\n-->\n<p>hi</p>\n", "html"), (1, 3, 0));
+        assert_eq!(counts("{}\n", "json"), (1, 0, 0));
+    }
+}
@@ -0,0 +1,665 @@
+//! Create, update, rename and delete agents and skills.
+//!
+//! Definitions are rendered from structured input, so the file name, the
+//! frontmatter `name` and the YAML itself always agree. Every change goes
+//! through `safe_write`: edits state the hash they expect to replace and
+//! deleted or replaced files are kept as backups. Only the project and user
+//! scopes are editable; plugin items belong to their plugin.
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::frontmatter::{self, AgentFrontmatter, ExtraKeys, SkillFrontmatter};
+use crate::role_templates::{self, RoleTemplate};
+use crate::safe_write::{self, Expected, WrittenFile};
+use crate::scopes::{self, ConfigRoot};
+use crate::skills::SKILL_FILE;
+use crate::{current_project_dir, project_key, AppState};
+
+const MAX_SLUG_LEN: usize = 64;
+
+/// An agent as edited in the UI
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AgentInput {
+    /// Becomes both the file name and the frontmatter `name`
+    pub name: String,
+    pub description: String,
+    /// `None` lets the agent use every tool
+    pub tools: Option<Vec<String>>,
+    pub model: Option<String>,
+    pub color: Option<String>,
+    /// System prompt
+    pub body: String,
+    /// Other frontmatter keys, written back unchanged
+    pub extra: ExtraKeys,
+}
+
+/// A skill as edited in the UI
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SkillInput {
+    /// Becomes both the directory name and the frontmatter `name`
+    pub name: String,
+    pub description: String,
+    pub allowed_tools: Option<Vec<String>>,
+    pub argument_hint: Option<String>,
+    pub model: Option<String>,
+    pub license: Option<String>,
+    pub version: Option<String>,
+    pub disable_model_invocation: Option<bool>,
+    pub body: String,
+    pub extra: ExtraKeys,
+}
+
+/// Payload of `claude-config-changed`
+#[derive(Clone, Serialize)]
+pub struct ConfigChangedEvent {
+    /// "agent" or "skill"
+    pub kind: String,
+    pub scope: String,
+    pub slug: String,
+    /// "created", "updated", "renamed" or "deleted"
+    pub action: String,
+    /// Previous slug, for renames
+    pub previous: Option<String>,
+}
+
+/// An editable config root and where its backups go
+struct Target {
+    root: ConfigRoot,
+    backups: PathBuf,
+    project_dir: Option<String>,
+}
+
+impl Target {
+    fn new(state: &AppState, scope: Option<&str>) -> Result<Self, String> {
+        match scope.unwrap_or("project") {
+            "project" => {
+                let dir = current_project_dir(state)?;
+                Ok(Target {
+                    root: ConfigRoot::project(&dir),
+                    backups: safe_write::backup_root(&project_key(&dir))?,
+                    project_dir: Some(dir),
+                })
+            }
+            "user" => Ok(Target {
+                root: ConfigRoot {
+                    scope: "user",
+                    dir: scopes::user_claude_dir().ok_or("Cannot find home directory")?,
+                    plugin: None,
+                },
+                backups: safe_write::backup_root("user")?,
+                project_dir: None,
+            }),
+            "plugin" => Err("Plugin agents and skills can't be edited here".to_string()),
+            other => Err(format!("Unknown scope: {}", other)),
+        }
+    }
+
+    /// Absolute path of `relative`, which must stay inside the root
+    fn path(&self, relative: &str) -> Result<PathBuf, String> {
+        let inside = Path::new(relative)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+        let path = self.root.dir.join(relative);
+        if !inside || !path.starts_with(&self.root.dir) {
+            return Err(format!("Invalid path: {}", relative));
+        }
+        Ok(path)
+    }
+
+    /// Backup and message name for a path inside the root
+    fn label(&self, relative: &str) -> String {
+        match self.project_dir {
+            Some(_) => format!(".claude/{}", relative),
+            None => relative.to_string(),
+        }
+    }
+
+    fn write(
+        &self,
+        app: &AppHandle,
+        relative: &str,
+        content: &str,
+        expected: &Expected,
+    ) -> Result<WrittenFile, String> {
+        match &self.project_dir {
+            Some(dir) => safe_write::write_in_project(
+                app,
+                dir,
+                &self.label(relative),
+                content.as_bytes(),
+                expected,
+            ),
+            None => safe_write::write_file(
+                &self.path(relative)?,
+                relative,
+                content.as_bytes(),
+                expected,
+                &self.backups,
+            ),
+        }
+    }
+
+    /// Move a file or directory out of the way, into the backups
+    fn remove(&self, relative: &str) -> Result<(), String> {
+        safe_write::remove_to_backup(&self.path(relative)?, &self.label(relative), &self.backups)
+            .map(|_| ())
+    }
+
+    fn notify(
+        &self,
+        app: &AppHandle,
+        kind: &str,
+        slug: &str,
+        action: &str,
+        previous: Option<&str>,
+    ) {
+        let _ = app.emit(
+            "claude-config-changed",
+            ConfigChangedEvent {
+                kind: kind.to_string(),
+                scope: self.root.scope.to_string(),
+                slug: slug.to_string(),
+                action: action.to_string(),
+                previous: previous.map(str::to_string),
+            },
+        );
+    }
+}
+
+/// Slugs are what Claude Code accepts as names: lowercase letters, digits
+/// and hyphens
+pub(crate) fn validate_slug(slug: &str) -> Result<(), String> {
+    if slug.is_empty() {
+        return Err("Name is required".to_string());
+    }
+    if slug.len() > MAX_SLUG_LEN {
+        return Err(format!("Name is longer than {} characters", MAX_SLUG_LEN));
+    }
+    let valid_chars = slug
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !valid_chars || slug.starts_with('-') || slug.ends_with('-') {
+        return Err(format!(
+            "Invalid name '{}': use lowercase letters, digits and hyphens",
+            slug
+        ));
+    }
+    Ok(())
+}
+
+fn text(value: &str) -> Option<Value> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| Value::String(value.to_string()))
+}
+
+/// Tool lists are written the way Claude Code's docs show them: `Read, Grep`
+fn tool_list(tools: &Option<Vec<String>>) -> Option<Value> {
+    tools.as_ref().map(|tools| Value::String(tools.join(", ")))
+}
+
+/// Markdown file with `fields` (in order, `None` skipped), then `extra`, as
+/// YAML frontmatter
+fn render(
+    fields: Vec<(&str, Option<Value>)>,
+    extra: &ExtraKeys,
+    body: &str,
+) -> Result<String, String> {
+    let mut mapping = Mapping::new();
+    for (key, value) in &fields {
+        if let Some(value) = value {
+            mapping.insert(Value::String(key.to_string()), value.clone());
+        }
+    }
+    for (key, value) in extra {
+        // Known keys always come from their fields
+        if !fields.iter().any(|(known, _)| known == key) {
+            mapping.insert(Value::String(key.clone()), value.clone());
+        }
+    }
+    let yaml = serde_yaml::to_string(&mapping).map_err(|e| e.to_string())?;
+    Ok(format!("---\n{}---\n\n{}\n", yaml, body.trim()))
+}
+
+fn render_agent(agent: &AgentInput) -> Result<String, String> {
+    render(
+        vec![
+            ("name", text(&agent.name)),
+            ("description", text(&agent.description)),
+            ("tools", tool_list(&agent.tools)),
+            ("model", agent.model.as_deref().and_then(text)),
+            ("color", agent.color.as_deref().and_then(text)),
+        ],
+        &agent.extra,
+        &agent.body,
+    )
+}
+
+fn render_skill(skill: &SkillInput) -> Result<String, String> {
+    render(
+        vec![
+            ("name", text(&skill.name)),
+            ("description", text(&skill.description)),
+            ("allowed-tools", tool_list(&skill.allowed_tools)),
+            (
+                "argument-hint",
+                skill.argument_hint.as_deref().and_then(text),
+            ),
+            ("model", skill.model.as_deref().and_then(text)),
+            ("license", skill.license.as_deref().and_then(text)),
+            ("version", skill.version.as_deref().and_then(text)),
+            (
+                "disable-model-invocation",
+                skill.disable_model_invocation.map(Value::Bool),
+            ),
+        ],
+        &skill.extra,
+        &skill.body,
+    )
+}
+
+fn template(id: Option<&str>) -> Result<Option<&'static RoleTemplate>, String> {
+    id.map(|id| role_templates::find(id).ok_or_else(|| format!("Unknown template: {}", id)))
+        .transpose()
+}
+
+fn template_tools(template: &RoleTemplate) -> Option<Vec<String>> {
+    (!template.tools.is_empty()).then(|| template.tools.iter().map(|t| t.to_string()).collect())
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Fill whatever the user left empty from the template
+fn apply_agent_template(agent: &mut AgentInput, template: &RoleTemplate) {
+    if agent.description.trim().is_empty() {
+        agent.description = template.description.to_string();
+    }
+    if agent.body.trim().is_empty() {
+        agent.body = template.body.to_string();
+    }
+    if agent.tools.is_none() {
+        agent.tools = template_tools(template);
+    }
+    if agent.model.is_none() {
+        agent.model = non_empty(template.model);
+    }
+    if agent.color.is_none() {
+        agent.color = non_empty(template.color);
+    }
+}
+
+fn apply_skill_template(skill: &mut SkillInput, template: &RoleTemplate) {
+    if skill.description.trim().is_empty() {
+        skill.description = template.description.to_string();
+    }
+    if skill.body.trim().is_empty() {
+        skill.body = template.body.to_string();
+    }
+    if skill.allowed_tools.is_none() {
+        skill.allowed_tools = template_tools(template);
+    }
+}
+
+fn agent_file(slug: &str) -> String {
+    format!("agents/{}.md", slug)
+}
+
+/// Where an existing skill's markdown lives: `skills/<slug>/SKILL.md`, or
+/// the single-file `skills/<slug>.md`. The first element is what to move or
+/// remove as a whole.
+fn skill_paths(target: &Target, slug: &str) -> Result<(String, String), String> {
+    let directory = format!("skills/{}", slug);
+    if target.path(&directory)?.is_dir() {
+        return Ok((directory.clone(), format!("{}/{}", directory, SKILL_FILE)));
+    }
+    let file = format!("skills/{}.md", slug);
+    if target.path(&file)?.is_file() {
+        return Ok((file.clone(), file));
+    }
+    Err(format!("Skill not found: {}", slug))
+}
+
+fn ensure_free(target: &Target, relative: &str, slug: &str) -> Result<(), String> {
+    if target.path(relative)?.exists() {
+        return Err(format!("'{}' already exists", slug));
+    }
+    Ok(())
+}
+
+fn read_existing(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))
+}
+
+/// Built-in role templates for new agents and skills
+#[tauri::command]
+pub fn list_role_templates() -> Vec<RoleTemplate> {
+    role_templates::TEMPLATES.to_vec()
+}
+
+/// Create an agent, optionally starting from a role template
+#[tauri::command]
+pub fn create_agent(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: Option<String>,
+    agent: AgentInput,
+    template: Option<String>,
+) -> Result<WrittenFile, String> {
+    let target = Target::new(&state, scope.as_deref())?;
+    let mut agent = agent;
+    agent.name = agent.name.trim().to_string();
+    validate_slug(&agent.name)?;
+    if let Some(template) = self::template(template.as_deref())? {
+        apply_agent_template(&mut agent, template);
+    }
+    let relative = agent_file(&agent.name);
+    ensure_free(&target, &relative, &agent.name)?;
+    let written = target.write(
+        &app,
+        &relative,
+        &render_agent(&agent)?,
+        &Expected::default(),
+    )?;
+    target.notify(&app, "agent", &agent.name, "created", None);
+    Ok(written)
+}
+
+/// Replace an agent's definition. Changing `name` renames the file too.
+#[tauri::command]
+pub fn update_agent(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: Option<String>,
+    slug: String,
+    agent: AgentInput,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    let target = Target::new(&state, scope.as_deref())?;
+    let expected = expected.unwrap_or_default();
+    let mut agent = agent;
+    agent.name = agent.name.trim().to_string();
+    validate_slug(&slug)?;
+    validate_slug(&agent.name)?;
+    let relative = agent_file(&slug);
+    if !target.path(&relative)?.is_file() {
+        return Err(format!("Agent not found: {}", slug));
+    }
+    let content = render_agent(&agent)?;
+
+    if agent.name == slug {
+        let written = target.write(&app, &relative, &content, &expected)?;
+        target.notify(&app, "agent", &slug, "updated", None);
+        return Ok(written);
+    }
+    let new_relative = agent_file(&agent.name);
+    ensure_free(&target, &new_relative, &agent.name)?;
+    let (from, to) = (target.path(&relative)?, target.path(&new_relative)?);
+    safe_write::check_expected(&from, &relative, &expected)?;
+    let current = crate::files::hash_file(&from)?;
+    std::fs::rename(&from, &to).map_err(|e| format!("Failed to rename {}: {}", slug, e))?;
+    let unchanged = Expected {
+        hash: Some(current),
+        mtime: None,
+    };
+    // Put the agent back where it was if the new content can't be written
+    let written = match target.write(&app, &new_relative, &content, &unchanged) {
+        Ok(written) => written,
+        Err(e) => {
+            let _ = std::fs::rename(&to, &from);
+            return Err(e);
+        }
+    };
+    target.notify(&app, "agent", &agent.name, "renamed", Some(&slug));
+    Ok(written)
+}
+
+/// Rename an agent, keeping the rest of its definition
+#[tauri::command]
+pub fn rename_agent(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: Option<String>,
+    slug: String,
+    new_slug: String,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    validate_slug(&slug)?;
+    let target = Target::new(&state, scope.as_deref())?;
+    let content = read_existing(&target.path(&agent_file(&slug))?)?;
+    let (fm, body, error) = frontmatter::parse::<AgentFrontmatter>(&content);
+    if let Some(error) = error {
+        return Err(format!(
+            "Fix the frontmatter of '{}' first: {}",
+            slug, error.message
+        ));
+    }
+    let agent = AgentInput {
+        name: new_slug,
+        description: fm.description.unwrap_or_default(),
+        tools: fm.tools,
+        model: fm.model,
+        color: fm.color,
+        body: body.to_string(),
+        extra: fm.extra,
+    };
+    update_agent(app, state, scope, slug, agent, expected)
+}
+
+/// Delete an agent. The file is moved to the backups.
+#[tauri::command]
+pub fn delete_agent(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: Option<String>,
+    slug: String,
+    expected: Option<Expected>,
+) -> Result<(), String> {
+    validate_slug(&slug)?;
+    let target = Target::new(&state, scope.as_deref())?;
+    let relative = agent_file(&slug);
+    let path = target.path(&relative)?;
+    if !path.is_file() {
+        return Err(format!("Agent not found: {}", slug));
+    }
+    safe_write::check_expected(&path, &relative, &expected.unwrap_or_default())?;
+    target.remove(&relative)?;
+    target.notify(&app, "agent", &slug, "deleted", None);
+    Ok(())
+}
+
+/// Create a directory skill (`skills/<name>/SKILL.md`), optionally starting
+/// from a role template
+#[tauri::command]
+pub fn create_skill(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: Option<String>,
+    skill: SkillInput,
+    template: Option<String>,
+) -> Result<WrittenFile, String> {
+    let target = Target::new(&state, scope.as_deref())?;
+    let mut skill = skill;
+    skill.name = skill.name.trim().to_string();
+    validate_slug(&skill.name)?;
+    if let Some(template) = self::template(template.as_deref())? {
+        apply_skill_template(&mut skill, template);
+    }
+    ensure_free(&target, &format!("skills/{}", skill.name), &skill.name)?;
+    ensure_free(&target, &format!("skills/{}.md", skill.name), &skill.name)?;
+    let relative = format!("skills/{}/{}", skill.name, SKILL_FILE);
+    let written = target.write(
+        &app,
+        &relative,
+        &render_skill(&skill)?,
+        &Expected::default(),
+    )?;
+    target.notify(&app, "skill", &skill.name, "created", None);
+    Ok(written)
+}
+
+/// Replace a skill's `SKILL.md` (or single file). Changing `name` renames
+/// the skill's directory too, bundled files included.
+#[tauri::command]
+pub fn update_skill(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: Option<String>,
+    slug: String,
+    skill: SkillInput,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    let target = Target::new(&state, scope.as_deref())?;
+    let expected = expected.unwrap_or_default();
+    let mut skill = skill;
+    skill.name = skill.name.trim().to_string();
+    validate_slug(&slug)?;
+    validate_slug(&skill.name)?;
+    let (container, markdown) = skill_paths(&target, &slug)?;
+    let content = render_skill(&skill)?;
+
+    if skill.name == slug {
+        let written = target.write(&app, &markdown, &content, &expected)?;
+        target.notify(&app, "skill", &slug, "updated", None);
+        return Ok(written);
+    }
+
+    safe_write::check_expected(&target.path(&markdown)?, &markdown, &expected)?;
+    let current = crate::files::hash_file(&target.path(&markdown)?)?;
+    let (new_container, new_markdown) = if container == markdown {
+        let file = format!("skills/{}.md", skill.name);
+        (file.clone(), file)
+    } else {
+        let directory = format!("skills/{}", skill.name);
+        (directory.clone(), format!("{}/{}", directory, SKILL_FILE))
+    };
+    ensure_free(&target, &format!("skills/{}", skill.name), &skill.name)?;
+    ensure_free(&target, &format!("skills/{}.md", skill.name), &skill.name)?;
+    let (from, to) = (target.path(&container)?, target.path(&new_container)?);
+    std::fs::rename(&from, &to).map_err(|e| format!("Failed to rename {}: {}", slug, e))?;
+    let unchanged = Expected {
+        hash: Some(current),
+        mtime: None,
+    };
+    // Put the skill back where it was if the new content can't be written
+    let written = match target.write(&app, &new_markdown, &content, &unchanged) {
+        Ok(written) => written,
+        Err(e) => {
+            let _ = std::fs::rename(&to, &from);
+            return Err(e);
+        }
+    };
+    target.notify(&app, "skill", &skill.name, "renamed", Some(&slug));
+    Ok(written)
+}
+
+/// Rename a skill, keeping the rest of its definition and bundled files
+#[tauri::command]
+pub fn rename_skill(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: Option<String>,
+    slug: String,
+    new_slug: String,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    validate_slug(&slug)?;
+    let target = Target::new(&state, scope.as_deref())?;
+    let (_, markdown) = skill_paths(&target, &slug)?;
+    let content = read_existing(&target.path(&markdown)?)?;
+    let (fm, body, error) = frontmatter::parse::<SkillFrontmatter>(&content);
+    if let Some(error) = error {
+        return Err(format!(
+            "Fix the frontmatter of '{}' first: {}",
+            slug, error.message
+        ));
+    }
+    let skill = SkillInput {
+        name: new_slug,
+        description: fm.description.unwrap_or_default(),
+        allowed_tools: fm.allowed_tools,
+        argument_hint: fm.argument_hint,
+        model: fm.model,
+        license: fm.license,
+        version: fm.version,
+        disable_model_invocation: fm.disable_model_invocation,
+        body: body.to_string(),
+        extra: fm.extra,
+    };
+    update_skill(app, state, scope, slug, skill, expected)
+}
+
+/// Delete a skill with everything bundled in its directory. It is moved to
+/// the backups.
+#[tauri::command]
+pub fn delete_skill(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: Option<String>,
+    slug: String,
+    expected: Option<Expected>,
+) -> Result<(), String> {
+    validate_slug(&slug)?;
+    let target = Target::new(&state, scope.as_deref())?;
+    let (container, markdown) = skill_paths(&target, &slug)?;
+    safe_write::check_expected(
+        &target.path(&markdown)?,
+        &markdown,
+        &expected.unwrap_or_default(),
+    )?;
+    target.remove(&container)?;
+    target.notify(&app, "skill", &slug, "deleted", None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> Target {
+        let dir = PathBuf::from("/home/user/.claude");
+        Target {
+            root: ConfigRoot {
+                scope: "user",
+                dir: dir.clone(),
+                plugin: None,
+            },
+            backups: dir.join("backups"),
+            project_dir: None,
+        }
+    }
+
+    #[test]
+    fn validate_slug_rejects_traversal() {
+        assert!(validate_slug("code-reviewer").is_ok());
+        for slug in ["", "../etc", "a/b", "Reviewer", "-x", "x-", "a.b"] {
+            assert!(validate_slug(slug).is_err(), "{}", slug);
+        }
+    }
+
+    #[test]
+    fn target_path_stays_inside_the_root() {
+        let target = target();
+        assert_eq!(
+            target.path("agents/reviewer.md").unwrap(),
+            target.root.dir.join("agents/reviewer.md")
+        );
+        for relative in [
+            "../outside.md",
+            "agents/../../x.md",
+            "/etc/passwd",
+            "./agents",
+        ] {
+            assert!(target.path(relative).is_err(), "{}", relative);
+        }
+        assert!(skill_paths(&target, "../../etc")
+            .unwrap_err()
+            .contains("Invalid path"));
+        assert!(target.path(&agent_file("../x")).is_err());
+    }
+}
@@ -0,0 +1,635 @@
+//! Language and framework detection.
+//!
+//! What to look for is data, not code: `detectors.json`, bundled with the
+//! app, lists every language and framework with what gives it away — the
+//! ecosystem of a parsed manifest, declared dependencies, marker files, keys
+//! in JSON, YAML or TOML files, text in build scripts and file extensions.
+//! `~/.cc-desktop/detectors.json` has the same shape; its entries replace
+//! bundled ones of the same name or are added after them. Every detection
+//! reports the evidence that triggered it.
+//!
+//! The registry is parsed once and only read again when the user's file
+//! changes; file extensions are counted in a single walk of the project and
+//! then split up per directory, so workspace members aren't walked again.
+
+use globset::Glob;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::manifests::Manifest;
+use crate::{app_data_dir, walk};
+
+const BUNDLED: &str = include_str!("detectors.json");
+
+/// Files with an extension needed before it counts, unless a detector says
+/// otherwise
+const MIN_FILES: usize = 3;
+
+/// Files looked at when counting extensions
+const MAX_FILES: usize = 20_000;
+
+/// A key in a JSON, YAML or TOML file, e.g. `["require", "laravel/framework"]`
+/// in composer.json
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeyRule {
+    pub file: String,
+    pub path: Vec<String>,
+    /// "runtime" (the default), "dev", "peer", "optional" or "build"
+    pub kind: Option<String>,
+}
+
+/// Text a file must contain
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ContainsRule {
+    pub file: String,
+    pub text: String,
+}
+
+/// One language or framework and what gives it away. File names are
+/// relative to the directory being analyzed and may use `*` in their last
+/// component.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Detector {
+    pub name: String,
+    /// Manifest ecosystems written in it, e.g. "node"
+    pub ecosystems: Vec<String>,
+    /// Dependencies by manifest ecosystem; a name ending in `*` matches by
+    /// prefix
+    pub packages: BTreeMap<String, Vec<String>>,
+    pub markers: Vec<String>,
+    pub keys: Vec<KeyRule>,
+    pub contains: Vec<ContainsRule>,
+    /// File extensions, without the dot
+    pub extensions: Vec<String>,
+    /// Files with those extensions needed; defaults to three
+    pub min_files: Option<usize>,
+}
+
+/// Languages and frameworks to look for, most specific framework first
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Registry {
+    pub languages: Vec<Detector>,
+    pub frameworks: Vec<Detector>,
+}
+
+/// The registry in effect and where the user's additions live
+#[derive(Clone, Serialize)]
+pub struct DetectorsInfo {
+    pub path: String,
+    pub exists: bool,
+    /// Why the user's file was ignored, if it was
+    pub error: Option<String>,
+    pub registry: Registry,
+}
+
+/// Why a language or framework was detected
+#[derive(Clone, Serialize)]
+pub struct Evidence {
+    /// "ecosystem", "dependency", "marker", "key", "content" or "extension"
+    pub kind: String,
+    /// File it was found in, relative to the project root
+    pub source: String,
+    /// The ecosystem, package, file, key, text or extension that matched
+    pub detail: String,
+}
+
+/// A language the project is written in
+#[derive(Clone, Serialize)]
+pub struct Language {
+    pub name: String,
+    pub evidence: Vec<Evidence>,
+}
+
+/// A framework the project uses
+#[derive(Clone, Serialize)]
+pub struct Framework {
+    pub name: String,
+    /// Package, or whatever else, that gave it away
+    pub package: String,
+    pub version: Option<String>,
+    /// "high" (runtime dependency), "medium" (dev, peer or optional
+    /// dependency, marker or build script) or "low" (indirect dependency or
+    /// file extensions only)
+    pub confidence: String,
+    /// File it was found in
+    pub source: String,
+    pub evidence: Vec<Evidence>,
+}
+
+/// Everything detected in a directory
+pub struct Stack {
+    /// Languages backed by manifests or markers in registry order, then
+    /// the ones only seen through file extensions, most files first
+    pub languages: Vec<Language>,
+    /// Most certain, then most specific, first
+    pub frameworks: Vec<Framework>,
+}
+
+impl Stack {
+    pub(crate) fn language_names(&self) -> Vec<String> {
+        self.languages.iter().map(|l| l.name.clone()).collect()
+    }
+}
+
+/// A piece of evidence with how strongly it counts
+struct Hit {
+    evidence: Evidence,
+    rank: u8,
+    version: Option<String>,
+    /// Files behind an extension hit
+    files: usize,
+}
+
+fn user_file() -> Result<PathBuf, String> {
+    Ok(app_data_dir()?.join("detectors.json"))
+}
+
+/// Replace detectors of the same name, add the rest at the end
+fn merge(into: &mut Vec<Detector>, extra: Vec<Detector>) {
+    for detector in extra {
+        match into.iter_mut().find(|d| d.name == detector.name) {
+            Some(existing) => *existing = detector,
+            None => into.push(detector),
+        }
+    }
+}
+
+fn bundled() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| serde_json::from_str(BUNDLED).expect("bundled detectors.json is valid"))
+}
+
+/// The bundled registry with the user's file merged in
+fn read_registry(path: Option<PathBuf>) -> DetectorsInfo {
+    let mut registry = bundled().clone();
+    let content = path.as_ref().and_then(|p| std::fs::read_to_string(p).ok());
+    let mut error = None;
+    if let Some(content) = &content {
+        match serde_json::from_str::<Registry>(content) {
+            Ok(user) => {
+                merge(&mut registry.languages, user.languages);
+                merge(&mut registry.frameworks, user.frameworks);
+            }
+            Err(e) => error = Some(e.to_string()),
+        }
+    }
+    DetectorsInfo {
+        path: path
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        exists: content.is_some(),
+        error,
+        registry,
+    }
+}
+
+/// The registry in effect; read again only when the user's file has been
+/// modified, created or removed since the last call
+fn load() -> Arc<DetectorsInfo> {
+    type Loaded = Option<(Option<SystemTime>, Arc<DetectorsInfo>)>;
+    static LOADED: OnceLock<Mutex<Loaded>> = OnceLock::new();
+
+    let path = user_file().ok();
+    let modified = path
+        .as_ref()
+        .and_then(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok());
+    let mut loaded = LOADED.get_or_init(Mutex::default).lock().unwrap();
+    if let Some((stamp, info)) = loaded.as_ref() {
+        if *stamp == modified {
+            return info.clone();
+        }
+    }
+    let info = Arc::new(read_registry(path));
+    *loaded = Some((modified, info.clone()));
+    info
+}
+
+/// The detectors in effect, for the settings screen
+#[tauri::command]
+pub fn list_detectors() -> Result<DetectorsInfo, String> {
+    Ok(load().as_ref().clone())
+}
+
+fn matches_package(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+/// How strongly a dependency of this kind counts
+fn dependency_rank(kind: &str) -> u8 {
+    match kind {
+        "runtime" | "build" => 3,
+        "indirect" => 1,
+        _ => 2,
+    }
+}
+
+fn confidence_label(rank: u8) -> &'static str {
+    match rank {
+        3 => "high",
+        2 => "medium",
+        _ => "low",
+    }
+}
+
+/// Existing files for a name that may have a `*` in its last component
+fn files(dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let pattern_path = Path::new(pattern);
+    let name = pattern_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let parent = dir.join(pattern_path.parent().unwrap_or(Path::new("")));
+    if !name.contains(['*', '?', '[']) {
+        let path = parent.join(&name);
+        return if path.exists() {
+            vec![path]
+        } else {
+            Vec::new()
+        };
+    }
+    let Ok(glob) = Glob::new(&name) else {
+        return Vec::new();
+    };
+    let matcher = glob.compile_matcher();
+    let mut found: Vec<PathBuf> = std::fs::read_dir(&parent)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| matcher.is_match(e.file_name()))
+                .map(|e| e.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    found.sort();
+    found
+}
+
+/// JSON, YAML or TOML file as a JSON value
+fn structured(path: &Path) -> Option<Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(&content).ok(),
+        Some("yaml" | "yml") => serde_yaml::from_str::<serde_yaml::Value>(&content)
+            .ok()
+            .and_then(|v| serde_json::to_value(v).ok()),
+        Some("toml") => toml::from_str::<toml::Table>(&content)
+            .ok()
+            .and_then(|v| serde_json::to_value(v).ok()),
+        _ => None,
+    }
+}
+
+/// Files per lowercase extension in every directory of a project, from one
+/// walk that honours ignore rules
+pub(crate) struct ExtensionCounts {
+    root: PathBuf,
+    /// By directory relative to the root, "" for the root itself; only
+    /// files directly inside each
+    dirs: HashMap<String, HashMap<String, usize>>,
+}
+
+impl ExtensionCounts {
+    pub(crate) fn scan(root: &Path) -> Self {
+        let mut dirs: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        let files = walk::walker(root)
+            .build()
+            .flatten()
+            .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+            .take(MAX_FILES);
+        for entry in files {
+            let (Some(parent), Some(ext)) = (entry.path().parent(), entry.path().extension())
+            else {
+                continue;
+            };
+            *dirs
+                .entry(walk::relative_path(root, parent))
+                .or_default()
+                .entry(ext.to_string_lossy().to_lowercase())
+                .or_insert(0) += 1;
+        }
+        ExtensionCounts {
+            root: root.to_path_buf(),
+            dirs,
+        }
+    }
+
+    /// Files per extension in `dir` and everything below it
+    fn under(&self, dir: &Path) -> HashMap<String, usize> {
+        let base = walk::relative_path(&self.root, dir);
+        let prefix = format!("{}/", base);
+        let mut counts = HashMap::new();
+        let inside = self
+            .dirs
+            .iter()
+            .filter(|(d, _)| base.is_empty() || **d == base || d.starts_with(&prefix));
+        for (_, dir_counts) in inside {
+            for (ext, count) in dir_counts {
+                *counts.entry(ext.clone()).or_insert(0) += count;
+            }
+        }
+        counts
+    }
+}
+
+struct Scan<'a> {
+    root: &'a Path,
+    dir: &'a Path,
+    manifests: &'a [Manifest],
+    extensions: HashMap<String, usize>,
+}
+
+impl Scan<'_> {
+    fn evidence(&self, kind: &str, source: &Path, detail: &str) -> Evidence {
+        Evidence {
+            kind: kind.to_string(),
+            source: walk::relative_path(self.root, source),
+            detail: detail.to_string(),
+        }
+    }
+
+    /// Everything that points to `detector`
+    fn hits(&self, detector: &Detector) -> Vec<Hit> {
+        let mut hits = Vec::new();
+        let hit = |evidence, rank, version| Hit {
+            evidence,
+            rank,
+            version,
+            files: 0,
+        };
+
+        for manifest in self.manifests {
+            if detector.ecosystems.contains(&manifest.ecosystem) {
+                let evidence = Evidence {
+                    kind: "ecosystem".to_string(),
+                    source: manifest.path.clone(),
+                    detail: manifest.ecosystem.clone(),
+                };
+                hits.push(hit(evidence, 3, None));
+            }
+            let Some(patterns) = detector.packages.get(&manifest.ecosystem) else {
+                continue;
+            };
+            for dep in &manifest.dependencies {
+                if patterns.iter().any(|p| matches_package(p, &dep.name)) {
+                    let evidence = Evidence {
+                        kind: "dependency".to_string(),
+                        source: manifest.path.clone(),
+                        detail: dep.name.clone(),
+                    };
+                    hits.push(hit(
+                        evidence,
+                        dependency_rank(&dep.kind),
+                        dep.version.clone(),
+                    ));
+                }
+            }
+        }
+
+        for marker in &detector.markers {
+            for path in files(self.dir, marker) {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                hits.push(hit(self.evidence("marker", &path, &name), 2, None));
+            }
+        }
+
+        for rule in &detector.keys {
+            for path in files(self.dir, &rule.file) {
+                let Some(mut value) = structured(&path) else {
+                    continue;
+                };
+                for key in &rule.path {
+                    value = match value.get(key) {
+                        Some(v) => v.clone(),
+                        None => Value::Null,
+                    };
+                }
+                if value.is_null() {
+                    continue;
+                }
+                let version = value
+                    .as_str()
+                    .or_else(|| value.get("version").and_then(Value::as_str))
+                    .map(str::to_string);
+                let detail = rule.path.last().cloned().unwrap_or_default();
+                let rank = dependency_rank(rule.kind.as_deref().unwrap_or("runtime"));
+                hits.push(hit(self.evidence("key", &path, &detail), rank, version));
+            }
+        }
+
+        for rule in &detector.contains {
+            for path in files(self.dir, &rule.file) {
+                let content = std::fs::read_to_string(&path).unwrap_or_default();
+                if content.contains(&rule.text) {
+                    hits.push(hit(self.evidence("content", &path, &rule.text), 2, None));
+                }
+            }
+        }
+
+        let min_files = detector.min_files.unwrap_or(MIN_FILES);
+        for ext in &detector.extensions {
+            let count = self.extensions.get(ext).copied().unwrap_or(0);
+            if count >= min_files.max(1) {
+                hits.push(Hit {
+                    evidence: self.evidence(
+                        "extension",
+                        self.dir,
+                        &format!("{} .{} files", count, ext),
+                    ),
+                    rank: 1,
+                    version: None,
+                    files: count,
+                });
+            }
+        }
+        hits
+    }
+}
+
+/// Languages and frameworks of `dir`, with `manifests` as read by
+/// `manifests::read_dir` and `extensions` counted from `root`. Sources are
+/// reported relative to `root`.
+pub(crate) fn detect(
+    root: &Path,
+    dir: &Path,
+    manifests: &[Manifest],
+    extensions: &ExtensionCounts,
+) -> Stack {
+    let info = load();
+    let registry = &info.registry;
+    let scan = Scan {
+        root,
+        dir,
+        manifests,
+        extensions: extensions.under(dir),
+    };
+
+    // (extension-only file count, registry order, language)
+    let mut languages: Vec<(Option<usize>, usize, Language)> = Vec::new();
+    for (order, detector) in registry.languages.iter().enumerate() {
+        let hits = scan.hits(detector);
+        if hits.is_empty() {
+            continue;
+        }
+        let extension_only = hits
+            .iter()
+            .all(|h| h.evidence.kind == "extension")
+            .then(|| hits.iter().map(|h| h.files).sum());
+        languages.push((
+            extension_only,
+            order,
+            Language {
+                name: detector.name.clone(),
+                evidence: hits.into_iter().map(|h| h.evidence).collect(),
+            },
+        ));
+    }
+    languages.sort_by_key(|(files, order, _)| match files {
+        None => (0, 0, *order),
+        Some(files) => (1, usize::MAX - files, *order),
+    });
+
+    let mut frameworks: Vec<(u8, usize, Framework)> = Vec::new();
+    for (order, detector) in registry.frameworks.iter().enumerate() {
+        let hits = scan.hits(detector);
+        // The strongest hit describes the framework; ties go to the first
+        let Some(best) = hits
+            .iter()
+            .reduce(|best, h| if h.rank > best.rank { h } else { best })
+        else {
+            continue;
+        };
+        let rank = best.rank;
+        let framework = Framework {
+            name: detector.name.clone(),
+            package: best.evidence.detail.clone(),
+            version: best.version.clone(),
+            confidence: confidence_label(rank).to_string(),
+            source: best.evidence.source.clone(),
+            evidence: hits.into_iter().map(|h| h.evidence).collect(),
+        };
+        frameworks.push((rank, order, framework));
+    }
+    frameworks.sort_by_key(|(rank, order, _)| (std::cmp::Reverse(*rank), *order));
+
+    Stack {
+        languages: languages.into_iter().map(|(_, _, l)| l).collect(),
+        frameworks: frameworks.into_iter().map(|(_, _, f)| f).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifests;
+    use crate::test_util::project;
+
+    fn detector(name: &str, extension: &str) -> Detector {
+        Detector {
+            name: name.to_string(),
+            extensions: vec![extension.to_string()],
+            ..Detector::default()
+        }
+    }
+
+    #[test]
+    fn bundled_registry_parses() {
+        let registry: Registry = serde_json::from_str(BUNDLED).unwrap();
+        assert!(!registry.languages.is_empty());
+        assert!(!registry.frameworks.is_empty());
+        for detectors in [&registry.languages, &registry.frameworks] {
+            let mut names: Vec<&str> = detectors.iter().map(|d| d.name.as_str()).collect();
+            names.sort_unstable();
+            names.dedup();
+            assert_eq!(names.len(), detectors.len(), "duplicate detector names");
+        }
+    }
+
+    #[test]
+    fn merge_replaces_by_name_and_appends_the_rest() {
+        let mut detectors = vec![detector("Rust", "rs"), detector("Go", "go")];
+        merge(
+            &mut detectors,
+            vec![detector("Rust", "rust"), detector("Zig", "zig")],
+        );
+        let merged: Vec<(&str, &str)> = detectors
+            .iter()
+            .map(|d| (d.name.as_str(), d.extensions[0].as_str()))
+            .collect();
+        assert_eq!(merged, vec![("Rust", "rust"), ("Go", "go"), ("Zig", "zig")]);
+    }
+
+    #[test]
+    fn extension_counts_under_a_directory() {
+        let counts = |entries: &[(&str, usize)]| {
+            entries
+                .iter()
+                .map(|(ext, n)| (ext.to_string(), *n))
+                .collect::<HashMap<_, _>>()
+        };
+        let extensions = ExtensionCounts {
+            root: PathBuf::from("/p"),
+            dirs: HashMap::from([
+                (String::new(), counts(&[("md", 1)])),
+                ("src".to_string(), counts(&[("rs", 2)])),
+                ("src/bin".to_string(), counts(&[("rs", 1), ("toml", 1)])),
+                ("srcx".to_string(), counts(&[("rs", 5)])),
+            ]),
+        };
+        assert_eq!(
+            extensions.under(Path::new("/p/src")),
+            counts(&[("rs", 3), ("toml", 1)])
+        );
+        assert_eq!(
+            extensions.under(Path::new("/p")),
+            counts(&[("md", 1), ("rs", 8), ("toml", 1)])
+        );
+        assert!(extensions.under(Path::new("/p/docs")).is_empty());
+    }
+
+    #[test]
+    fn detect_orders_frameworks_by_confidence_then_registry() {
+        let root = project(
+            "detectors-order",
+            &[(
+                "package.json",
+                r#"{
+                    "dependencies": {"express": "^4.19.0", "react": "^18.2.0"},
+                    "devDependencies": {"next": "^14.0.0"}
+                }"#,
+            )],
+        );
+        let found = manifests::read_dir(&root, &root);
+        let extensions = ExtensionCounts::scan(&root);
+        let stack = detect(&root, &root, &found, &extensions);
+        let frameworks: Vec<(&str, &str)> = stack
+            .frameworks
+            .iter()
+            .map(|f| (f.name.as_str(), f.confidence.as_str()))
+            .collect();
+        // Next.js comes first in the registry but is only a dev dependency
+        assert_eq!(
+            frameworks,
+            vec![
+                ("React", "high"),
+                ("Express", "high"),
+                ("Next.js", "medium")
+            ]
+        );
+        assert_eq!(stack.frameworks[0].source, "package.json");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,474 @@
+//! Lazily-loaded project file tree and confined file reads.
+//!
+//! The sidebar asks for one directory at a time. Listings honour the same
+//! ignore rules as every other scan and carry enough metadata (kind, size,
+//! mtime, symlink, git status) to render the tree without extra calls.
+//! Every path coming from the frontend goes through `resolve_in_project`,
+//! so nothing outside the project can be listed or read.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tauri::State;
+
+use crate::{current_project_dir, git, walk, AppState};
+
+/// Entries returned per directory unless the caller asks otherwise
+const DEFAULT_LIMIT: usize = 500;
+/// Hard cap so a huge generated directory can't stall the UI
+const MAX_LIMIT: usize = 5000;
+/// Bytes read from a file unless the caller asks otherwise
+const DEFAULT_READ_BYTES: u64 = 2 * 1024 * 1024;
+/// Hard cap on a single read
+const MAX_READ_BYTES: u64 = 16 * 1024 * 1024;
+
+/// One entry in a directory listing
+#[derive(Clone, Serialize)]
+pub struct FileNode {
+    pub name: String,
+    /// Project-relative path with forward slashes
+    pub path: String,
+    /// "dir", "file" or "other". Symlinks report their target's kind.
+    pub kind: String,
+    pub size: u64,
+    /// Last modification, Unix milliseconds
+    pub mtime: u64,
+    pub is_symlink: bool,
+    /// "modified", "added", "deleted", "renamed", "untracked" or "conflicted"
+    pub git_status: Option<String>,
+}
+
+/// Children of one directory
+#[derive(Clone, Serialize)]
+pub struct DirListing {
+    pub path: String,
+    pub entries: Vec<FileNode>,
+    /// Number of visible entries in the directory
+    pub total: usize,
+    /// More entries exist than were returned
+    pub truncated: bool,
+}
+
+/// Resolve a project-relative path to an absolute one, refusing anything that
+/// escapes the project through `..`, absolute paths or symlinks.
+/// The target doesn't need to exist yet; its closest existing ancestor is
+/// what gets checked.
+pub(crate) fn resolve_in_project(project_dir: &str, relative: &str) -> Result<PathBuf, String> {
+    let root = std::fs::canonicalize(project_dir).map_err(|e| e.to_string())?;
+    let relative = Path::new(relative);
+    if relative.components().any(|c| {
+        matches!(
+            c,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    }) {
+        return Err(format!(
+            "Path is outside the project: {}",
+            relative.display()
+        ));
+    }
+
+    let joined = root.join(relative);
+    let mut existing = joined.as_path();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        let Some(parent) = existing.parent() else {
+            break;
+        };
+        rest.push(existing.file_name().unwrap_or_default().to_os_string());
+        existing = parent;
+    }
+    let mut resolved = std::fs::canonicalize(existing).map_err(|e| e.to_string())?;
+    if !resolved.starts_with(&root) {
+        return Err(format!(
+            "Path is outside the project: {}",
+            relative.display()
+        ));
+    }
+    for part in rest.into_iter().rev() {
+        resolved.push(part);
+    }
+    Ok(resolved)
+}
+
+pub(crate) fn mtime_millis(meta: &std::fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn status_label(code: &str) -> &'static str {
+    let mut chars = code.chars();
+    let (x, y) = (chars.next().unwrap_or(' '), chars.next().unwrap_or(' '));
+    match (x, y) {
+        ('?', '?') => "untracked",
+        ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => "conflicted",
+        ('R', _) | ('C', _) => "renamed",
+        ('A', _) => "added",
+        ('D', _) | (_, 'D') => "deleted",
+        _ => "modified",
+    }
+}
+
+/// Git status of paths under `dir`, keyed by path relative to `root`.
+/// Untracked directories show up once with a trailing `/`.
+fn git_statuses(root: &Path, dir: &Path) -> HashMap<String, &'static str> {
+    let mut statuses = HashMap::new();
+    let Ok(toplevel) = git::run(root, &["rev-parse", "--show-toplevel"]) else {
+        return statuses;
+    };
+    let toplevel = std::fs::canonicalize(toplevel).unwrap_or_default();
+    let dir_str = dir.to_string_lossy().to_string();
+    let Ok(output) = git::output(git::command(root).args([
+        "status",
+        "--porcelain=v1",
+        "-z",
+        "--untracked-files=normal",
+        "--",
+        &dir_str,
+    ])) else {
+        return statuses;
+    };
+
+    let text = String::from_utf8_lossy(&output);
+    let mut records = text.split('\0');
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let (code, path) = record.split_at(3);
+        if code.starts_with('R') || code.starts_with('C') {
+            // Renames carry the original path as a second record
+            records.next();
+        }
+        let absolute = toplevel.join(path);
+        if let Ok(relative) = absolute.strip_prefix(root) {
+            let mut key = relative.to_string_lossy().replace('\\', "/");
+            if path.ends_with('/') {
+                key.push('/');
+            }
+            statuses.insert(key, status_label(code.trim_end()));
+        }
+    }
+    statuses
+}
+
+/// Status for one node: its own entry, an untracked ancestor, or — for
+/// directories — "modified" when anything inside has a status
+fn node_status(
+    statuses: &HashMap<String, &'static str>,
+    path: &str,
+    is_dir: bool,
+) -> Option<String> {
+    if let Some(status) = statuses.get(path) {
+        return Some(status.to_string());
+    }
+    let as_dir = format!("{}/", path);
+    if let Some(status) = statuses.get(&as_dir) {
+        return Some(status.to_string());
+    }
+    let untracked_ancestor = statuses
+        .iter()
+        .any(|(p, s)| *s == "untracked" && p.ends_with('/') && path.starts_with(p.as_str()));
+    if untracked_ancestor {
+        return Some("untracked".to_string());
+    }
+    if is_dir && statuses.keys().any(|p| p.starts_with(&as_dir)) {
+        return Some("modified".to_string());
+    }
+    None
+}
+
+/// List one directory of the project. `path` is project-relative; empty or
+/// missing means the project root.
+#[tauri::command]
+pub fn list_dir(
+    state: State<AppState>,
+    path: Option<String>,
+    limit: Option<usize>,
+) -> Result<DirListing, String> {
+    let project = current_project_dir(&state)?;
+    let root = std::fs::canonicalize(&project).map_err(|e| e.to_string())?;
+    let relative = path.unwrap_or_default();
+    let relative = relative.trim_matches('/');
+    let dir = resolve_in_project(&project, relative)?;
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", relative));
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    // Sort on kind and name first; full metadata only for what gets returned
+    let mut children: Vec<(ignore::DirEntry, bool)> = walk::walker(&dir)
+        .max_depth(Some(1))
+        .build()
+        .flatten()
+        .filter(|entry| entry.depth() == 1)
+        .map(|entry| {
+            let is_dir = entry.path().is_dir();
+            (entry, is_dir)
+        })
+        .collect();
+    children.sort_by(|(a, a_dir), (b, b_dir)| {
+        b_dir.cmp(a_dir).then_with(|| {
+            a.file_name()
+                .to_string_lossy()
+                .to_lowercase()
+                .cmp(&b.file_name().to_string_lossy().to_lowercase())
+        })
+    });
+
+    let total = children.len();
+    let statuses = git_statuses(&root, &dir);
+    let entries = children
+        .into_iter()
+        .take(limit)
+        .map(|(entry, is_dir)| {
+            let node_path = walk::relative_path(&root, entry.path());
+            let meta = std::fs::metadata(entry.path()).ok();
+            let kind = if is_dir {
+                "dir"
+            } else if meta.as_ref().is_some_and(|m| m.is_file()) {
+                "file"
+            } else {
+                "other"
+            };
+            FileNode {
+                name: entry.file_name().to_string_lossy().to_string(),
+                git_status: node_status(&statuses, &node_path, is_dir),
+                path: node_path,
+                kind: kind.to_string(),
+                size: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+                mtime: meta.as_ref().map(mtime_millis).unwrap_or(0),
+                is_symlink: entry.path_is_symlink(),
+            }
+        })
+        .collect();
+
+    Ok(DirListing {
+        path: relative.to_string(),
+        entries,
+        total,
+        truncated: total > limit,
+    })
+}
+
+/// 1-based, inclusive line range
+#[derive(Clone, Deserialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: Option<usize>,
+}
+
+/// Contents of a project file
+#[derive(Clone, Serialize)]
+pub struct FileContent {
+    pub path: String,
+    pub size: u64,
+    /// Last modification, Unix milliseconds
+    pub mtime: u64,
+    /// SHA-256 of the whole file, for conflict checks on write
+    pub hash: String,
+    pub binary: bool,
+    /// Detected text encoding, e.g. "UTF-8", "UTF-16LE", "Shift_JIS"
+    pub encoding: Option<String>,
+    pub has_bom: bool,
+    /// "lf", "crlf" or "mixed"; `None` for single-line or binary files
+    pub line_ending: Option<String>,
+    /// Language id for syntax highlighting, from the file name
+    pub language: Option<String>,
+    /// Text of the requested lines; `None` for binary files
+    pub content: Option<String>,
+    /// First and last line included in `content` (1-based)
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Lines in the part of the file that was read
+    pub total_lines: usize,
+    /// The size cap cut the file short
+    pub truncated: bool,
+}
+
+/// Language id (as used by the frontend's highlighter) for a file name
+pub(crate) fn language_for(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    let by_name = match name.as_str() {
+        "dockerfile" | "containerfile" => Some("docker"),
+        "makefile" | "gnumakefile" => Some("makefile"),
+        "cmakelists.txt" => Some("cmake"),
+        "gemfile" | "rakefile" => Some("ruby"),
+        _ if name.starts_with("dockerfile.") => Some("docker"),
+        _ if name.starts_with(".env") => Some("bash"),
+        _ => None,
+    };
+    if by_name.is_some() {
+        return by_name;
+    }
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    Some(match ext.as_str() {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "py" | "pyi" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "scala" => "scala",
+        "rb" => "ruby",
+        "php" => "php",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => "cpp",
+        "cs" => "csharp",
+        "m" | "mm" => "objectivec",
+        "dart" => "dart",
+        "ex" | "exs" => "elixir",
+        "erl" | "hrl" => "erlang",
+        "hs" => "haskell",
+        "lua" => "lua",
+        "r" => "r",
+        "pl" | "pm" => "perl",
+        "sh" | "bash" | "zsh" => "bash",
+        "ps1" | "psm1" => "powershell",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "sass" => "sass",
+        "less" => "less",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "json" | "jsonc" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "xml" | "plist" | "svg" => "xml",
+        "md" | "mdx" => "markdown",
+        "tf" | "tfvars" | "hcl" => "hcl",
+        "graphql" | "gql" => "graphql",
+        "proto" => "protobuf",
+        "ini" | "cfg" => "ini",
+        _ => return None,
+    })
+}
+
+/// Decode file bytes: BOM first, then strict UTF-8, then a statistical guess.
+/// Returns (text, encoding name, had BOM), or `None` for binary data.
+fn decode_text(bytes: &[u8]) -> Option<(String, &'static str, bool)> {
+    if let Some((encoding, bom_len)) = encoding_rs::Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return Some((text.into_owned(), encoding.name(), true));
+    }
+    if walk::looks_binary(bytes) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some((text.to_string(), "UTF-8", false)),
+        // A multi-byte character cut off by the size cap is still UTF-8
+        Err(e) if e.error_len().is_none() => {
+            let text = String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned();
+            Some((text, "UTF-8", false))
+        }
+        Err(_) => {
+            let mut detector = chardetng::EncodingDetector::new();
+            detector.feed(bytes, true);
+            let encoding = detector.guess(None, true);
+            let (text, _) = encoding.decode_without_bom_handling(bytes);
+            Some((text.into_owned(), encoding.name(), false))
+        }
+    }
+}
+
+fn detect_line_ending(text: &str) -> Option<String> {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    match (crlf, lf) {
+        (0, 0) => None,
+        (_, 0) => Some("crlf".to_string()),
+        (0, _) => Some("lf".to_string()),
+        _ => Some("mixed".to_string()),
+    }
+}
+
+/// SHA-256 of a file's contents, hex encoded
+pub(crate) fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Read a file inside the project. `range` selects lines; `max_bytes` caps
+/// how much of the file is read (default 2 MiB).
+#[tauri::command]
+pub fn read_project_file(
+    state: State<AppState>,
+    path: String,
+    range: Option<LineRange>,
+    max_bytes: Option<u64>,
+) -> Result<FileContent, String> {
+    let project = current_project_dir(&state)?;
+    let absolute = resolve_in_project(&project, &path)?;
+    let meta = std::fs::metadata(&absolute).map_err(|e| e.to_string())?;
+    if !meta.is_file() {
+        return Err(format!("Not a file: {}", path));
+    }
+
+    let cap = max_bytes
+        .unwrap_or(DEFAULT_READ_BYTES)
+        .clamp(1, MAX_READ_BYTES);
+    let mut bytes = Vec::new();
+    std::fs::File::open(&absolute)
+        .and_then(|f| f.take(cap).read_to_end(&mut bytes))
+        .map_err(|e| e.to_string())?;
+    let truncated = meta.len() > bytes.len() as u64;
+
+    let mut content = FileContent {
+        path: path.clone(),
+        size: meta.len(),
+        mtime: mtime_millis(&meta),
+        hash: hash_file(&absolute)?,
+        binary: true,
+        encoding: None,
+        has_bom: false,
+        line_ending: None,
+        language: language_for(&absolute).map(str::to_string),
+        content: None,
+        start_line: 0,
+        end_line: 0,
+        total_lines: 0,
+        truncated,
+    };
+    let Some((text, encoding, has_bom)) = decode_text(&bytes) else {
+        return Ok(content);
+    };
+
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let total_lines = lines.len();
+    let (start, end) = match range {
+        Some(range) => {
+            let start = range.start.max(1);
+            let end = range.end.unwrap_or(total_lines).min(total_lines);
+            (start, end)
+        }
+        None => (1, total_lines),
+    };
+    let selected = if start <= end {
+        lines[start - 1..end].concat()
+    } else {
+        String::new()
+    };
+
+    content.binary = false;
+    content.encoding = Some(encoding.to_string());
+    content.has_bom = has_bom;
+    content.line_ending = detect_line_ending(&text);
+    content.content = Some(selected);
+    content.start_line = if start <= end { start } else { 0 };
+    content.end_line = if start <= end { end } else { 0 };
+    content.total_lines = total_lines;
+    Ok(content)
+}
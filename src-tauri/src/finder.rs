@@ -0,0 +1,360 @@
+//! Fuzzy file finder for `@file` mentions and quick-open.
+//!
+//! Keeps an in-memory, ignore-aware index of the project's file paths. The
+//! index is built on first use and kept current from the filesystem watcher,
+//! so queries never rescan the tree.
+
+use serde::Serialize;
+use std::path::Path;
+use tauri::State;
+
+use crate::watcher::FsChangedEvent;
+use crate::{current_project_dir, files, now_millis, walk, AppState};
+
+/// Paths beyond this are left out of the index
+const MAX_INDEXED: usize = 200_000;
+const DEFAULT_LIMIT: usize = 50;
+
+// Scoring weights. Every matched character earns MATCH plus the bonus for
+// where it sits; gaps between matched characters cost points.
+const MATCH: i32 = 16;
+const SEGMENT_START: i32 = 24;
+const WORD_START: i32 = 16;
+const CAMEL_BOUNDARY: i32 = 16;
+const CONSECUTIVE: i32 = 12;
+const IN_FILE_NAME: i32 = 8;
+const EXACT_CASE: i32 = 1;
+const GAP_START: i32 = 6;
+const GAP_EXTEND: i32 = 1;
+/// Bonus for a file modified just now, fading out over RECENCY_WINDOW_MS
+const RECENCY: i32 = 24;
+const RECENCY_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+struct IndexedFile {
+    path: String,
+    mtime: u64,
+}
+
+/// Path index for one project
+pub struct FileIndex {
+    root: String,
+    files: Vec<IndexedFile>,
+}
+
+/// One ranked result
+#[derive(Clone, Serialize)]
+pub struct FileMatch {
+    pub path: String,
+    pub score: i32,
+    /// Character indices into `path` that matched the query
+    pub positions: Vec<usize>,
+}
+
+impl FileIndex {
+    fn build(root: &str) -> Self {
+        let root_path = Path::new(root);
+        let files = walk::walker(root_path)
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .take(MAX_INDEXED)
+            .map(|entry| IndexedFile {
+                path: walk::relative_path(root_path, entry.path()),
+                mtime: entry.metadata().map_or(0, |m| files::mtime_millis(&m)),
+            })
+            .collect();
+        FileIndex {
+            root: root.to_string(),
+            files,
+        }
+    }
+
+    fn remove(&mut self, path: &str) {
+        let dir_prefix = format!("{}/", path);
+        self.files
+            .retain(|f| f.path != path && !f.path.starts_with(&dir_prefix));
+    }
+
+    fn upsert(&mut self, path: &str) {
+        let absolute = Path::new(&self.root).join(path);
+        if absolute.is_dir() {
+            // A directory appeared (e.g. moved in): drop whatever was
+            // indexed under it once, then index what's inside
+            self.remove(path);
+            let root = Path::new(&self.root).to_path_buf();
+            let room = MAX_INDEXED.saturating_sub(self.files.len());
+            let files = walk::walker(&absolute)
+                .build()
+                .flatten()
+                .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+                .take(room)
+                .map(|entry| IndexedFile {
+                    mtime: entry.metadata().map_or(0, |m| files::mtime_millis(&m)),
+                    path: walk::relative_path(&root, entry.path()),
+                });
+            self.files.extend(files);
+            return;
+        }
+        let mtime = std::fs::metadata(&absolute).map_or(0, |m| files::mtime_millis(&m));
+        if let Some(file) = self.files.iter_mut().find(|f| f.path == path) {
+            file.mtime = mtime;
+        } else if self.files.len() < MAX_INDEXED {
+            self.files.push(IndexedFile {
+                path: path.to_string(),
+                mtime,
+            });
+        }
+    }
+
+    /// Apply a batch of watcher changes
+    pub(crate) fn apply(&mut self, event: &FsChangedEvent) {
+        for path in &event.deleted {
+            self.remove(path);
+        }
+        for rename in &event.renamed {
+            self.remove(&rename.from);
+            self.upsert(&rename.to);
+        }
+        for path in event.created.iter().chain(&event.modified) {
+            self.upsert(path);
+        }
+    }
+
+    pub(crate) fn root(&self) -> &str {
+        &self.root
+    }
+}
+
+/// Bonus for matching at position `i` of `path`
+fn position_bonus(path: &[char], i: usize, file_name_start: usize) -> i32 {
+    let mut bonus = MATCH;
+    let current = path[i];
+    match i.checked_sub(1).map(|p| path[p]) {
+        None | Some('/') | Some('\\') => bonus += SEGMENT_START,
+        Some('_' | '-' | '.' | ' ') => bonus += WORD_START,
+        Some(prev) if prev.is_lowercase() && current.is_uppercase() => bonus += CAMEL_BOUNDARY,
+        Some(prev) if !prev.is_ascii_digit() && current.is_ascii_digit() => {
+            bonus += CAMEL_BOUNDARY / 2
+        }
+        _ => {}
+    }
+    if i >= file_name_start {
+        bonus += IN_FILE_NAME;
+    }
+    bonus
+}
+
+/// Best alignment of `query` (already lowercased) as a subsequence of `path`.
+/// Returns the score and matched character positions.
+fn score_path(path: &str, query: &[char], query_raw: &[char]) -> Option<(i32, Vec<usize>)> {
+    let chars: Vec<char> = path.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let (n, m) = (chars.len(), query.len());
+    if m == 0 || m > n {
+        return None;
+    }
+
+    // Quick reject: query must be a subsequence
+    let mut qi = 0;
+    for c in &lower {
+        if qi < m && *c == query[qi] {
+            qi += 1;
+        }
+    }
+    if qi < m {
+        return None;
+    }
+
+    let file_name_start = chars
+        .iter()
+        .rposition(|c| *c == '/')
+        .map(|p| p + 1)
+        .unwrap_or(0);
+
+    // best[j][i]: best score with query[j] matched at path[i]
+    // from[j][i]: where query[j - 1] was matched on that best path
+    const NONE: i32 = i32::MIN / 2;
+    let mut best = vec![vec![NONE; n]; m];
+    let mut from = vec![vec![usize::MAX; n]; m];
+
+    for j in 0..m {
+        // Running max of best[j-1][k] + GAP_EXTEND * k over k <= i - 2
+        let mut run = NONE;
+        let mut run_at = usize::MAX;
+        for i in j..n {
+            if j > 0 && i >= 2 {
+                let k = i - 2;
+                let candidate = best[j - 1][k].saturating_add(GAP_EXTEND * k as i32);
+                if best[j - 1][k] > NONE && candidate > run {
+                    run = candidate;
+                    run_at = k;
+                }
+            }
+            if lower[i] != query[j] {
+                continue;
+            }
+            let mut score = position_bonus(&chars, i, file_name_start);
+            if chars[i] == query_raw[j] {
+                score += EXACT_CASE;
+            }
+            if j == 0 {
+                best[j][i] = score;
+                continue;
+            }
+            let adjacent = if best[j - 1][i - 1] > NONE {
+                best[j - 1][i - 1] + CONSECUTIVE
+            } else {
+                NONE
+            };
+            let gapped = if run > NONE {
+                run - GAP_EXTEND * (i as i32 - 1) - GAP_START
+            } else {
+                NONE
+            };
+            if adjacent == NONE && gapped == NONE {
+                continue;
+            }
+            if adjacent >= gapped {
+                best[j][i] = adjacent + score;
+                from[j][i] = i - 1;
+            } else {
+                best[j][i] = gapped + score;
+                from[j][i] = run_at;
+            }
+        }
+    }
+
+    let (mut end, score) = best[m - 1]
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| **s > NONE)
+        .max_by_key(|(_, s)| **s)
+        .map(|(i, s)| (i, *s))?;
+
+    let mut positions = vec![0; m];
+    for j in (0..m).rev() {
+        positions[j] = end;
+        end = from[j][end];
+    }
+    Some((score, positions))
+}
+
+fn recency_bonus(mtime: u64, now: u64) -> i32 {
+    let age = now.saturating_sub(mtime);
+    if age >= RECENCY_WINDOW_MS {
+        return 0;
+    }
+    (RECENCY as u64 * (RECENCY_WINDOW_MS - age) / RECENCY_WINDOW_MS) as i32
+}
+
+/// Rank indexed paths against `query`
+fn search(index: &FileIndex, query: &str, limit: usize) -> Vec<FileMatch> {
+    let query_raw: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let query_lower: Vec<char> = query_raw
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let now = now_millis();
+
+    if query_lower.is_empty() {
+        // No query: most recently modified first
+        let mut recent: Vec<&IndexedFile> = index.files.iter().collect();
+        recent.sort_by_key(|f| std::cmp::Reverse(f.mtime));
+        return recent
+            .into_iter()
+            .take(limit)
+            .map(|f| FileMatch {
+                path: f.path.clone(),
+                score: recency_bonus(f.mtime, now),
+                positions: Vec::new(),
+            })
+            .collect();
+    }
+
+    let mut matches: Vec<FileMatch> = index
+        .files
+        .iter()
+        .filter_map(|f| {
+            let (score, positions) = score_path(&f.path, &query_lower, &query_raw)?;
+            // Prefer shorter paths when everything else is equal
+            let length_penalty = (f.path.len() / 8) as i32;
+            Some(FileMatch {
+                path: f.path.clone(),
+                score: score + recency_bonus(f.mtime, now) - length_penalty,
+                positions,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    matches.truncate(limit);
+    matches
+}
+
+/// Fuzzy-find project files by path. Building the index walks the whole
+/// tree, so that happens off the main thread and outside the lock.
+#[tauri::command]
+pub async fn find_files(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FileMatch>, String> {
+    let dir = current_project_dir(&state)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    {
+        let index = state.file_index.lock().unwrap();
+        if let Some(index) = index.as_ref().filter(|i| i.root() == dir) {
+            return Ok(search(index, &query, limit));
+        }
+    }
+    let built = {
+        let dir = dir.clone();
+        tokio::task::spawn_blocking(move || FileIndex::build(&dir))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    let mut index = state.file_index.lock().unwrap();
+    // Another call may have finished first; keep whichever index is current
+    if index.as_ref().map(|i| i.root()) != Some(dir.as_str()) {
+        *index = Some(built);
+    }
+    let index = index.as_ref().ok_or("File index unavailable")?;
+    Ok(search(index, &query, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(path: &str, query: &str) -> Option<(i32, Vec<usize>)> {
+        let raw: Vec<char> = query.chars().collect();
+        let lower: Vec<char> = query.to_lowercase().chars().collect();
+        score_path(path, &lower, &raw)
+    }
+
+    #[test]
+    fn score_path_needs_a_subsequence() {
+        assert!(score("src/main.rs", "xyz").is_none());
+        assert!(score("src/main.rs", "").is_none());
+        assert!(score("a.rs", "a.rs.bak").is_none());
+        let (_, positions) = score("src/main.rs", "main").unwrap();
+        assert_eq!(positions, vec![4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn score_path_prefers_file_names_and_boundaries() {
+        let in_name = score("lib/btn.ts", "btn").unwrap().0;
+        let in_dirs = score("btn/lib.ts", "btn").unwrap().0;
+        assert!(in_name > in_dirs);
+
+        let camel = score("src/FileIndex.rs", "fi").unwrap().0;
+        let inner = score("src/profile.rs", "fi").unwrap().0;
+        assert!(camel > inner);
+
+        let exact_case = score("src/Main.rs", "M").unwrap().0;
+        let other_case = score("src/main.rs", "M").unwrap().0;
+        assert!(exact_case > other_case);
+    }
+}
@@ -0,0 +1,295 @@
+//! YAML frontmatter for agent, skill and command markdown files.
+//!
+//! A file may open with a `---` line, a YAML mapping and a closing `---`
+//! line; everything after that is the body. Known keys land in typed fields
+//! and anything else is kept in `extra` so editing a file never drops keys
+//! the app doesn't know about. Errors carry the line number in the file.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Keys the app doesn't model, kept as-is
+pub type ExtraKeys = BTreeMap<String, serde_yaml::Value>;
+
+/// A frontmatter problem, located in the file
+#[derive(Clone, Debug, Serialize)]
+pub struct ParseError {
+    pub message: String,
+    /// 1-based line in the file, when known
+    pub line: Option<usize>,
+}
+
+/// A tool list written either as `Read, Grep` or as a YAML list
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+enum ToolList {
+    Text(String),
+    List(Vec<String>),
+}
+
+fn tool_list<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let tools = Option::<ToolList>::deserialize(deserializer)?;
+    Ok(tools.map(|tools| match tools {
+        ToolList::Text(text) => text
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        ToolList::List(list) => list,
+    }))
+}
+
+/// A string that may have been written as a bare number (`version: 1.0`)
+fn scalar_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde_yaml::Value;
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) => Some(s),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        Some(Value::Bool(b)) => Some(b.to_string()),
+        Some(_) => return Err(serde::de::Error::custom("expected a string")),
+    })
+}
+
+/// Frontmatter of `.claude/agents/*.md`
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct AgentFrontmatter {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Tools the agent may use; `None` means all tools
+    #[serde(default, deserialize_with = "tool_list")]
+    pub tools: Option<Vec<String>>,
+    pub model: Option<String>,
+    pub color: Option<String>,
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
+}
+
+/// Frontmatter of skills and slash commands
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SkillFrontmatter {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "allowed-tools", default, deserialize_with = "tool_list")]
+    pub allowed_tools: Option<Vec<String>>,
+    #[serde(rename = "argument-hint")]
+    pub argument_hint: Option<String>,
+    pub model: Option<String>,
+    pub license: Option<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub version: Option<String>,
+    #[serde(rename = "disable-model-invocation")]
+    pub disable_model_invocation: Option<bool>,
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
+}
+
+/// Frontmatter source split from the body
+struct Split<'a> {
+    /// YAML text and the file line it starts on
+    yaml: Option<(&'a str, usize)>,
+    body: &'a str,
+}
+
+fn is_fence(line: &str) -> bool {
+    line.trim_end() == "---"
+}
+
+/// Separate the frontmatter block from the body. The block must start on the
+/// first non-blank line; a `---` later in the body is just markdown.
+fn split(content: &str) -> Result<Split<'_>, ParseError> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut offset = 0;
+    let mut line_no = 0;
+    let mut lines = content.split_inclusive('\n');
+
+    // Find the opening fence
+    let opened = loop {
+        let Some(line) = lines.next() else {
+            break false;
+        };
+        line_no += 1;
+        offset += line.len();
+        if line.trim().is_empty() {
+            continue;
+        }
+        break is_fence(line);
+    };
+    if !opened {
+        return Ok(Split {
+            yaml: None,
+            body: content,
+        });
+    }
+
+    let yaml_start = offset;
+    let yaml_line = line_no + 1;
+    for line in lines {
+        if is_fence(line) {
+            return Ok(Split {
+                yaml: Some((&content[yaml_start..offset], yaml_line)),
+                body: &content[offset + line.len()..],
+            });
+        }
+        offset += line.len();
+    }
+    Err(ParseError {
+        message: "Frontmatter is missing its closing `---`".to_string(),
+        line: Some(yaml_line - 1),
+    })
+}
+
+/// Quote plain top-level values that contain `: `, which strict YAML rejects
+/// but hand-written agent files often have (`description: Use when: ...`)
+fn quote_loose_values(yaml: &str) -> String {
+    yaml.lines()
+        .map(|line| {
+            let starts_key = line
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_');
+            let Some((key, value)) = line.split_once(": ").filter(|_| starts_key) else {
+                return line.to_string();
+            };
+            let value = value.trim();
+            let plain = !value.starts_with(['"', '\'', '[', '{', '|', '>', '&', '*', '!']);
+            if plain && value.contains(": ") {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                format!("{}: \"{}\"", key, escaped)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn from_yaml<T: DeserializeOwned + Default>(
+    yaml: &str,
+    first_line: usize,
+) -> Result<T, ParseError> {
+    // Pad with blank lines so the parser's own line numbers (in the message
+    // too) match the file
+    let yaml = format!("{}{}", "\n".repeat(first_line - 1), yaml);
+    let to_error = |e: serde_yaml::Error| ParseError {
+        line: e.location().map(|l| l.line()),
+        message: e.to_string(),
+    };
+    // Quoting keeps the line count, so error lines stay accurate
+    let (source, value) = match serde_yaml::from_str::<serde_yaml::Value>(&yaml) {
+        Ok(value) => (yaml, value),
+        Err(e) => {
+            let quoted = quote_loose_values(&yaml);
+            let value = serde_yaml::from_str(&quoted).map_err(|_| to_error(e))?;
+            (quoted, value)
+        }
+    };
+    match value {
+        serde_yaml::Value::Null => Ok(T::default()),
+        serde_yaml::Value::Mapping(_) => serde_yaml::from_str(&source).map_err(to_error),
+        _ => Err(ParseError {
+            message: "Frontmatter must be a mapping of `key: value` pairs".to_string(),
+            line: Some(first_line),
+        }),
+    }
+}
+
+/// Parse a markdown file's frontmatter into `T` and return it with the body.
+/// On error the defaults are returned alongside the error, so one bad file
+/// doesn't hide the rest of a listing.
+pub(crate) fn parse<T: DeserializeOwned + Default>(content: &str) -> (T, &str, Option<ParseError>) {
+    match split(content) {
+        Ok(Split { yaml: None, body }) => (T::default(), body, None),
+        Ok(Split {
+            yaml: Some((yaml, first_line)),
+            body,
+        }) => match from_yaml(yaml, first_line) {
+            Ok(value) => (value, body, None),
+            Err(e) => (T::default(), body, Some(e)),
+        },
+        Err(e) => (T::default(), content, Some(e)),
+    }
+}
+
+/// First non-empty line of a markdown body, without heading marks, as a
+/// fallback description
+pub(crate) fn first_body_line(body: &str) -> String {
+    body.lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or("")
+        .to_string()
+}
+
+/// File line of a top-level frontmatter key, for pointing diagnostics at it
+pub(crate) fn key_line(content: &str, key: &str) -> Option<usize> {
+    let Ok(Split {
+        yaml: Some((yaml, first_line)),
+        ..
+    }) = split(content)
+    else {
+        return None;
+    };
+    yaml.lines()
+        .position(|line| {
+            line.strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })
+        .map(|index| first_line + index)
+}
+
+/// Line the frontmatter block opens on, or `None` without frontmatter
+pub(crate) fn start_line(content: &str) -> Option<usize> {
+    match split(content) {
+        Ok(Split {
+            yaml: Some((_, first_line)),
+            ..
+        }) => Some(first_line - 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reports_file_lines() {
+        let content = "\n---\nname: review\ndisable-model-invocation: maybe\n---\nBody\n";
+        let (skill, body, error) = parse::<SkillFrontmatter>(content);
+        assert_eq!(body, "Body\n");
+        assert!(skill.name.is_none());
+        assert_eq!(error.unwrap().line, Some(4));
+    }
+
+    #[test]
+    fn parse_reports_missing_fence_and_non_mapping() {
+        let (_, body, error) = parse::<AgentFrontmatter>("---\nname: a\nBody\n");
+        assert_eq!(body, "---\nname: a\nBody\n");
+        assert_eq!(error.unwrap().line, Some(1));
+
+        let (_, _, error) = parse::<AgentFrontmatter>("---\n- a\n- b\n---\n");
+        assert_eq!(error.unwrap().line, Some(2));
+    }
+
+    #[test]
+    fn parse_quotes_loose_values_and_keeps_extra_keys() {
+        let content = "---\ndescription: Use when: reviewing\ntools: Read, Grep\ncolor: blue\nowner: me\n---\n";
+        let (agent, _, error) = parse::<AgentFrontmatter>(content);
+        assert!(error.is_none());
+        assert_eq!(agent.description.as_deref(), Some("Use when: reviewing"));
+        assert_eq!(
+            agent.tools,
+            Some(vec!["Read".to_string(), "Grep".to_string()])
+        );
+        assert!(agent.extra.contains_key("owner"));
+        assert_eq!(key_line(content, "color"), Some(4));
+    }
+}
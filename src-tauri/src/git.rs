@@ -0,0 +1,96 @@
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Identity used when the app has to commit on the user's behalf
+/// and git has no user.name/user.email configured, and for checkpoints.
+pub(crate) const FALLBACK_NAME: &str = "CC Desktop";
+pub(crate) const FALLBACK_EMAIL: &str = "cc-desktop@localhost";
+
+/// Build a git command that runs in `dir`
+pub(crate) fn command(dir: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(dir);
+    // Never block on an editor or credential prompt from a GUI app
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+    cmd.env("GIT_EDITOR", "true");
+    cmd
+}
+
+/// Run a prepared git command and return raw stdout.
+/// Non-zero exit is turned into an error carrying git's stderr.
+pub(crate) fn output(cmd: &mut Command) -> Result<Vec<u8>, String> {
+    let output = cmd.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            "git is not installed or not on PATH".to_string()
+        } else {
+            format!("Failed to run git: {}", e)
+        }
+    })?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Err(if !stderr.is_empty() {
+            stderr
+        } else if !stdout.is_empty() {
+            stdout
+        } else {
+            format!("git exited with {}", output.status)
+        })
+    }
+}
+
+/// Run git in `dir` and return stdout as text with the trailing newline removed
+pub(crate) fn run(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let stdout = output(command(dir).args(args))?;
+    Ok(String::from_utf8_lossy(&stdout).trim_end().to_string())
+}
+
+/// Whether `dir` is inside a git work tree
+pub(crate) fn is_repo(dir: &Path) -> bool {
+    run(dir, &["rev-parse", "--is-inside-work-tree"])
+        .map(|out| out == "true")
+        .unwrap_or(false)
+}
+
+/// `-c user.name=… -c user.email=…` overrides, only for the parts the
+/// repo's own config doesn't already provide
+pub(crate) fn identity_args(dir: &Path) -> Vec<String> {
+    let mut args = Vec::new();
+    if run(dir, &["config", "user.name"]).map_or(true, |v| v.is_empty()) {
+        args.push("-c".to_string());
+        args.push(format!("user.name={}", FALLBACK_NAME));
+    }
+    if run(dir, &["config", "user.email"]).map_or(true, |v| v.is_empty()) {
+        args.push("-c".to_string());
+        args.push(format!("user.email={}", FALLBACK_EMAIL));
+    }
+    args
+}
+
+/// Per-file line stats from `git diff --numstat`
+#[derive(Clone, Serialize)]
+pub struct FileStat {
+    pub path: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Parse `git diff --numstat` output. Binary files report 0/0.
+pub(crate) fn parse_numstat(text: &str) -> Vec<FileStat> {
+    text.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let added = parts.next()?;
+            let deleted = parts.next()?;
+            let path = parts.next()?;
+            Some(FileStat {
+                path: path.to_string(),
+                additions: added.parse().unwrap_or(0),
+                deletions: deleted.parse().unwrap_or(0),
+            })
+        })
+        .collect()
+}
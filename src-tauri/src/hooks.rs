@@ -0,0 +1,560 @@
+//! Claude Code hooks from the settings files.
+//!
+//! Hooks live under `"hooks"` in each settings file, grouped by event and
+//! then by matcher:
+//!
+//! ```json
+//! { "hooks": { "PreToolUse": [
+//!     { "matcher": "Edit|Write", "hooks": [{ "type": "command", "command": "npm run lint" }] }
+//! ] } }
+//! ```
+//!
+//! Each hook is addressed by `<scope>:<event>:<group>:<index>`. Edits are
+//! validated against the schema Claude Code expects and written back through
+//! `settings`, leaving the rest of the file alone. `test_hook` runs a hook's
+//! command the way Claude Code would, with a synthetic event on stdin.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+use tokio::io::AsyncWriteExt;
+
+use crate::safe_write::{Expected, WrittenFile};
+use crate::settings::{self, SettingsFileInfo};
+use crate::{current_project_dir, resolve_full_path, AppState};
+
+/// Hook events and whether they filter on a matcher
+const EVENTS: &[(&str, bool)] = &[
+    ("PreToolUse", true),
+    ("PostToolUse", true),
+    ("Notification", false),
+    ("UserPromptSubmit", false),
+    ("Stop", false),
+    ("SubagentStop", false),
+    ("PreCompact", true),
+    ("SessionStart", true),
+    ("SessionEnd", false),
+];
+/// Claude Code's default hook timeout
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+/// Output kept from a test run
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// One hook command
+#[derive(Clone, Serialize)]
+pub struct HookInfo {
+    pub id: String,
+    /// Settings scope: "local", "project" or "user"
+    pub scope: String,
+    /// Settings file the hook is defined in
+    pub path: String,
+    pub event: String,
+    /// Tool (or trigger) pattern; `None` matches everything
+    pub matcher: Option<String>,
+    /// Hook type, normally "command"
+    pub kind: String,
+    pub command: Option<String>,
+    /// Seconds before Claude Code kills the command
+    pub timeout: Option<u64>,
+    /// Other keys on the hook, kept as-is
+    pub extra: Map<String, Value>,
+}
+
+/// All hooks, plus the state of each settings file
+#[derive(Clone, Serialize)]
+pub struct HooksOverview {
+    pub files: Vec<SettingsFileInfo>,
+    /// Grouped by event in Claude Code's order, highest-precedence file first
+    pub hooks: Vec<HookInfo>,
+}
+
+/// A hook as edited in the UI
+#[derive(Clone, Deserialize)]
+pub struct HookInput {
+    pub event: String,
+    #[serde(default)]
+    pub matcher: Option<String>,
+    pub command: String,
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// Outcome of `test_hook`
+#[derive(Clone, Serialize)]
+pub struct HookTestResult {
+    /// `None` when the command was killed or didn't start
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+    pub timed_out: bool,
+    /// Exit code 2 tells Claude Code to block the action
+    pub blocking: bool,
+    /// Stdout parsed as JSON, for hooks that answer with a decision object
+    pub json_output: Option<Value>,
+    /// The payload that was sent on stdin
+    pub payload: Value,
+}
+
+/// Parsed `<scope>:<event>:<group>:<index>`
+struct HookId<'a> {
+    scope: &'a str,
+    event: &'a str,
+    group: usize,
+    index: usize,
+}
+
+fn parse_id(id: &str) -> Result<HookId<'_>, String> {
+    let invalid = || format!("Invalid hook id: {}", id);
+    let mut parts = id.splitn(4, ':');
+    let (Some(scope), Some(event), Some(group), Some(index)) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    Ok(HookId {
+        scope,
+        event,
+        group: group.parse().map_err(|_| invalid())?,
+        index: index.parse().map_err(|_| invalid())?,
+    })
+}
+
+fn uses_matcher(event: &str) -> Option<bool> {
+    EVENTS.iter().find(|(e, _)| *e == event).map(|(_, m)| *m)
+}
+
+/// Empty and `*` matchers both mean "everything"
+fn normalize_matcher(matcher: Option<&str>) -> Option<String> {
+    matcher
+        .map(str::trim)
+        .filter(|m| !m.is_empty() && *m != "*")
+        .map(str::to_string)
+}
+
+fn validate(hook: &HookInput) -> Result<(), String> {
+    let Some(takes_matcher) = uses_matcher(&hook.event) else {
+        let names: Vec<&str> = EVENTS.iter().map(|(e, _)| *e).collect();
+        return Err(format!(
+            "Unknown hook event '{}'; expected one of {}",
+            hook.event,
+            names.join(", ")
+        ));
+    };
+    if let Some(matcher) = normalize_matcher(hook.matcher.as_deref()) {
+        if !takes_matcher {
+            return Err(format!("{} hooks don't take a matcher", hook.event));
+        }
+        Regex::new(&matcher).map_err(|e| format!("Invalid matcher '{}': {}", matcher, e))?;
+    }
+    if hook.command.trim().is_empty() {
+        return Err("Hook command is empty".to_string());
+    }
+    if hook.timeout == Some(0) {
+        return Err("Timeout must be at least one second".to_string());
+    }
+    Ok(())
+}
+
+/// Hooks defined in one settings file
+fn collect(scope: &str, path: &str, settings: &Map<String, Value>) -> Vec<HookInfo> {
+    let mut hooks = Vec::new();
+    let Some(events) = settings.get("hooks").and_then(Value::as_object) else {
+        return hooks;
+    };
+    for (event, groups) in events {
+        let Some(groups) = groups.as_array() else {
+            continue;
+        };
+        for (group_index, group) in groups.iter().enumerate() {
+            let matcher = normalize_matcher(group.get("matcher").and_then(Value::as_str));
+            let entries = group.get("hooks").and_then(Value::as_array);
+            for (index, entry) in entries.into_iter().flatten().enumerate() {
+                let Some(entry) = entry.as_object() else {
+                    continue;
+                };
+                let mut extra = entry.clone();
+                extra.remove("type");
+                extra.remove("command");
+                extra.remove("timeout");
+                hooks.push(HookInfo {
+                    id: format!("{}:{}:{}:{}", scope, event, group_index, index),
+                    scope: scope.to_string(),
+                    path: path.to_string(),
+                    event: event.clone(),
+                    matcher: matcher.clone(),
+                    kind: entry
+                        .get("type")
+                        .and_then(Value::as_str)
+                        .unwrap_or("command")
+                        .to_string(),
+                    command: entry
+                        .get("command")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    timeout: entry.get("timeout").and_then(Value::as_u64),
+                    extra,
+                });
+            }
+        }
+    }
+    hooks
+}
+
+fn find_hook(project_dir: &str, id: &str) -> Result<HookInfo, String> {
+    let parsed = parse_id(id)?;
+    let file = settings::settings_file(project_dir, parsed.scope)?;
+    let loaded = file.load();
+    collect(parsed.scope, &loaded.info.path, &loaded.settings)
+        .into_iter()
+        .find(|hook| hook.id == id)
+        .ok_or_else(|| format!("Hook not found: {}", id))
+}
+
+/// The `hooks.<event>` array, created if missing
+fn event_groups<'a>(
+    settings: &'a mut Map<String, Value>,
+    event: &str,
+) -> Result<&'a mut Vec<Value>, String> {
+    let hooks = settings
+        .entry("hooks")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or("`hooks` in settings is not an object")?;
+    hooks
+        .entry(event)
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or_else(|| format!("`hooks.{}` in settings is not a list", event))
+}
+
+/// Append a hook to the group with its matcher, creating the group if needed
+fn insert(settings: &mut Map<String, Value>, hook: &HookInput, entry: Value) -> Result<(), String> {
+    let matcher = normalize_matcher(hook.matcher.as_deref());
+    let groups = event_groups(settings, &hook.event)?;
+    let existing = groups
+        .iter_mut()
+        .find(|group| normalize_matcher(group.get("matcher").and_then(Value::as_str)) == matcher);
+    match existing
+        .and_then(|group| group.get_mut("hooks"))
+        .and_then(Value::as_array_mut)
+    {
+        Some(entries) => entries.push(entry),
+        None => {
+            let mut group = Map::new();
+            if let Some(matcher) = matcher {
+                group.insert("matcher".to_string(), Value::String(matcher));
+            }
+            group.insert("hooks".to_string(), Value::Array(vec![entry]));
+            groups.push(Value::Object(group));
+        }
+    }
+    Ok(())
+}
+
+/// Take a hook out of its group, dropping groups and events left empty.
+/// Returns the removed entry.
+fn take(settings: &mut Map<String, Value>, id: &HookId) -> Result<Value, String> {
+    let not_found = || "Hook not found; the settings file may have changed".to_string();
+    let groups = event_groups(settings, id.event)?;
+    let entries = groups
+        .get_mut(id.group)
+        .and_then(|group| group.get_mut("hooks"))
+        .and_then(Value::as_array_mut)
+        .ok_or_else(not_found)?;
+    if id.index >= entries.len() {
+        return Err(not_found());
+    }
+    let entry = entries.remove(id.index);
+    if entries.is_empty() {
+        groups.remove(id.group);
+    }
+    if groups.is_empty() {
+        if let Some(hooks) = settings.get_mut("hooks").and_then(Value::as_object_mut) {
+            hooks.shift_remove(id.event);
+            if hooks.is_empty() {
+                settings.shift_remove("hooks");
+            }
+        }
+    }
+    Ok(entry)
+}
+
+/// Hook entry JSON, keeping unknown keys from `base`
+fn entry_json(hook: &HookInput, base: Option<Value>) -> Value {
+    let mut entry = match base {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    entry.insert("type".to_string(), json!("command"));
+    entry.insert("command".to_string(), json!(hook.command.trim()));
+    match hook.timeout {
+        Some(timeout) => entry.insert("timeout".to_string(), json!(timeout)),
+        None => entry.shift_remove("timeout"),
+    };
+    Value::Object(entry)
+}
+
+/// List hooks from the local, project and user settings
+#[tauri::command]
+pub fn list_hooks(state: State<AppState>) -> Result<HooksOverview, String> {
+    let dir = current_project_dir(&state)?;
+    let mut files = Vec::new();
+    let mut hooks = Vec::new();
+    for file in settings::settings_files(&dir) {
+        let loaded = file.load();
+        hooks.extend(collect(file.scope, &loaded.info.path, &loaded.settings));
+        files.push(loaded.info);
+    }
+    let order = |event: &str| {
+        EVENTS
+            .iter()
+            .position(|(e, _)| *e == event)
+            .unwrap_or(EVENTS.len())
+    };
+    hooks.sort_by_key(|hook| order(&hook.event));
+    Ok(HooksOverview { files, hooks })
+}
+
+/// Add a hook to a settings file ("local", "project" or "user")
+#[tauri::command]
+pub fn add_hook(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: String,
+    hook: HookInput,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    validate(&hook)?;
+    let dir = current_project_dir(&state)?;
+    let file = settings::settings_file(&dir, &scope)?;
+    let mut loaded = file.load_for_edit()?;
+    insert(&mut loaded.settings, &hook, entry_json(&hook, None))?;
+    file.save(&app, &loaded, &expected.unwrap_or_default())
+}
+
+/// Replace a hook. Changing its event or matcher moves it to that group.
+#[tauri::command]
+pub fn update_hook(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+    hook: HookInput,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    validate(&hook)?;
+    let dir = current_project_dir(&state)?;
+    let parsed = parse_id(&id)?;
+    let file = settings::settings_file(&dir, parsed.scope)?;
+    let mut loaded = file.load_for_edit()?;
+    let current = find_hook(&dir, &id)?;
+    let moved = current.event != hook.event
+        || current.matcher != normalize_matcher(hook.matcher.as_deref());
+
+    if moved {
+        let old = take(&mut loaded.settings, &parsed)?;
+        insert(&mut loaded.settings, &hook, entry_json(&hook, Some(old)))?;
+    } else {
+        let entry = event_groups(&mut loaded.settings, parsed.event)?
+            .get_mut(parsed.group)
+            .and_then(|group| group.get_mut("hooks"))
+            .and_then(Value::as_array_mut)
+            .and_then(|entries| entries.get_mut(parsed.index))
+            .ok_or("Hook not found; the settings file may have changed")?;
+        *entry = entry_json(&hook, Some(entry.take()));
+    }
+    file.save(&app, &loaded, &expected.unwrap_or_default())
+}
+
+/// Remove a hook
+#[tauri::command]
+pub fn remove_hook(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    let dir = current_project_dir(&state)?;
+    let parsed = parse_id(&id)?;
+    let file = settings::settings_file(&dir, parsed.scope)?;
+    let mut loaded = file.load_for_edit()?;
+    take(&mut loaded.settings, &parsed)?;
+    file.save(&app, &loaded, &expected.unwrap_or_default())
+}
+
+/// Tool name to put in a synthetic event: the first literal alternative of
+/// the matcher, or Bash
+fn sample_tool(matcher: Option<&str>) -> String {
+    matcher
+        .and_then(|m| m.split('|').next())
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty() && m.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        .unwrap_or_else(|| "Bash".to_string())
+}
+
+/// The JSON Claude Code would send on stdin for this hook's event
+fn sample_event(hook: &HookInfo, cwd: &str) -> Value {
+    let mut event = json!({
+        "session_id": "cc-desktop-test",
+        "transcript_path": "",
+        "cwd": cwd,
+        "hook_event_name": hook.event,
+    });
+    let tool = sample_tool(hook.matcher.as_deref());
+    let tool_input = match tool.as_str() {
+        "Bash" => json!({ "command": "echo hello", "description": "Print hello" }),
+        "Edit" | "MultiEdit" => {
+            json!({ "file_path": format!("{}/README.md", cwd), "old_string": "a", "new_string": "b" })
+        }
+        "Write" => json!({ "file_path": format!("{}/example.txt", cwd), "content": "hello" }),
+        "Read" => json!({ "file_path": format!("{}/README.md", cwd) }),
+        _ => json!({}),
+    };
+    let extra = match hook.event.as_str() {
+        "PreToolUse" => json!({ "tool_name": tool, "tool_input": tool_input }),
+        "PostToolUse" => json!({
+            "tool_name": tool,
+            "tool_input": tool_input,
+            "tool_response": { "success": true }
+        }),
+        "Notification" => json!({ "message": "Claude needs your permission to use Bash" }),
+        "UserPromptSubmit" => json!({ "prompt": "Write a function that adds two numbers" }),
+        "Stop" | "SubagentStop" => json!({ "stop_hook_active": false }),
+        "PreCompact" => {
+            json!({ "trigger": hook.matcher.as_deref().unwrap_or("manual"), "custom_instructions": "" })
+        }
+        "SessionStart" => json!({ "source": hook.matcher.as_deref().unwrap_or("startup") }),
+        "SessionEnd" => json!({ "reason": "other" }),
+        _ => json!({}),
+    };
+    if let (Some(event), Value::Object(extra)) = (event.as_object_mut(), extra) {
+        event.extend(extra);
+    }
+    event
+}
+
+/// Shallow-merge the caller's payload over the synthetic one
+fn merge_payload(base: Value, overrides: Option<Value>) -> Value {
+    match (base, overrides) {
+        (Value::Object(mut base), Some(Value::Object(overrides))) => {
+            base.extend(overrides);
+            Value::Object(base)
+        }
+        (_, Some(overrides)) => overrides,
+        (base, None) => base,
+    }
+}
+
+fn clip_output(bytes: &[u8]) -> String {
+    let end = bytes.len().min(MAX_OUTPUT_BYTES);
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+/// Run a hook's command once with a synthetic event on stdin. Fields in
+/// `sample_payload` replace the generated ones.
+#[tauri::command]
+pub async fn test_hook(
+    state: State<'_, AppState>,
+    id: String,
+    sample_payload: Option<Value>,
+) -> Result<HookTestResult, String> {
+    let dir = current_project_dir(&state)?;
+    let hook = find_hook(&dir, &id)?;
+    let command = hook
+        .command
+        .clone()
+        .ok_or_else(|| format!("Only command hooks can be tested, not '{}'", hook.kind))?;
+    let payload = merge_payload(sample_event(&hook, &dir), sample_payload);
+    let timeout = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS));
+
+    #[cfg(not(target_os = "windows"))]
+    let mut process = tokio::process::Command::new("sh");
+    #[cfg(not(target_os = "windows"))]
+    process.args(["-c", &command]);
+    #[cfg(target_os = "windows")]
+    let mut process = tokio::process::Command::new("cmd");
+    #[cfg(target_os = "windows")]
+    process.args(["/C", &command]);
+
+    let started = Instant::now();
+    let mut child = process
+        .current_dir(&dir)
+        .env("PATH", resolve_full_path())
+        .env("CLAUDE_PROJECT_DIR", &dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start hook: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that never reads stdin is fine; ignore broken pipes
+        let _ = stdin.write_all(payload.to_string().as_bytes()).await;
+    }
+    let (exit_code, stdout, stderr, timed_out) =
+        match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(output) => {
+                let output = output.map_err(|e| e.to_string())?;
+                (output.status.code(), output.stdout, output.stderr, false)
+            }
+            // Dropping the child kills it
+            Err(_) => (None, Vec::new(), Vec::new(), true),
+        };
+
+    let stdout = clip_output(&stdout);
+    Ok(HookTestResult {
+        exit_code,
+        json_output: serde_json::from_str(stdout.trim()).ok(),
+        stdout,
+        stderr: clip_output(&stderr),
+        duration_ms: started.elapsed().as_millis() as u64,
+        timed_out,
+        blocking: exit_code == Some(2),
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(event: &str, matcher: Option<&str>, command: &str) -> HookInput {
+        HookInput {
+            event: event.to_string(),
+            matcher: matcher.map(str::to_string),
+            command: command.to_string(),
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn insert_groups_by_matcher_and_take_restores() {
+        let mut settings = Map::new();
+        settings.insert("model".to_string(), json!("opus"));
+        let original = settings.clone();
+
+        let first = hook("PreToolUse", Some("Bash"), "./check.sh");
+        let second = hook("PreToolUse", Some("Bash"), "./log.sh");
+        insert(&mut settings, &first, entry_json(&first, None)).unwrap();
+        insert(&mut settings, &second, entry_json(&second, None)).unwrap();
+        let groups = settings["hooks"]["PreToolUse"].as_array().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0]["hooks"].as_array().unwrap().len(), 2);
+
+        let id = |index| HookId {
+            scope: "project",
+            event: "PreToolUse",
+            group: 0,
+            index,
+        };
+        let taken = take(&mut settings, &id(1)).unwrap();
+        assert_eq!(taken["command"], "./log.sh");
+        take(&mut settings, &id(0)).unwrap();
+        assert_eq!(settings, original);
+        assert!(take(&mut settings, &id(0)).is_err());
+    }
+}
@@ -4,6 +4,38 @@ use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, State};
 use tokio::io::{AsyncBufReadExt, BufReader};
 
+mod agents;
+mod changes;
+mod checkpoint;
+mod claude_md;
+mod code_stats;
+mod config_editor;
+mod detectors;
+mod files;
+mod finder;
+mod frontmatter;
+mod git;
+mod hooks;
+mod lint;
+mod manifests;
+mod mcp;
+mod memory;
+mod permissions;
+mod role_templates;
+mod safe_write;
+mod scopes;
+mod search;
+mod settings;
+mod skills;
+mod slash_commands;
+#[cfg(test)]
+mod test_util;
+mod tooling;
+mod walk;
+mod watcher;
+mod workspaces;
+mod worktree;
+
 #[cfg(not(target_os = "windows"))]
 extern crate libc;
 
@@ -20,6 +52,22 @@ pub struct ChatMessage {
 pub struct AppState {
     pub project_dir: Mutex<Option<String>>,
     pub child_pid: Mutex<Option<u32>>,
+    /// Worktree id that runs are isolated into, if any
+    pub active_worktree: Mutex<Option<String>>,
+    /// Id of the run in progress, if any
+    pub current_run: Mutex<Option<String>>,
+    /// File change reports of recent runs
+    pub run_reports: Mutex<changes::RunReports>,
+    /// Filesystem watcher for the current project
+    pub watcher: Mutex<Option<watcher::ProjectWatcher>>,
+    /// Path index for `find_files`, built on first use
+    pub file_index: Mutex<Option<finder::FileIndex>>,
+    /// Content search in progress, if any
+    pub active_search: Mutex<Option<search::ActiveSearch>>,
+    /// Last code statistics, reused while the tree is unchanged
+    pub code_stats: Mutex<Option<code_stats::StatsCache>>,
+    /// Last memory overview, to resolve reads and writes against
+    pub memory_overview: Mutex<Option<memory::OverviewCache>>,
 }
 
 // =============================================================================
@@ -155,6 +203,80 @@ fn projects_base_dir() -> Result<PathBuf, String> {
     Ok(home.join("Documents").join("CC-Projects"))
 }
 
+/// Get the app-managed data directory (worktrees, checkpoints, caches)
+fn app_data_dir() -> Result<PathBuf, String> {
+    let home = home_dir().ok_or("Cannot find home directory")?;
+    Ok(home.join(".cc-desktop"))
+}
+
+/// Stable per-project key for app data: folder name plus a short path hash
+fn project_key(project_dir: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let path = Path::new(project_dir);
+    let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let hash = format!("{:x}", Sha256::digest(canonical.to_string_lossy().as_bytes()));
+    let name = slugify(
+        &canonical
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy(),
+    );
+    format!("{}-{}", name, &hash[..12])
+}
+
+/// Sanitize a name: lowercase, replace spaces with hyphens, remove special chars
+fn slugify(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else if c == ' ' {
+                '-'
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Current time as Unix seconds
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Current time as Unix milliseconds
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Read the current project directory or fail with the standard message
+fn current_project_dir(state: &AppState) -> Result<String, String> {
+    state
+        .project_dir
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "No project directory set".to_string())
+}
+
+/// Directory runs execute in: the active worktree, or the project itself
+fn active_run_dir(state: &AppState) -> Result<String, String> {
+    let dir = current_project_dir(state)?;
+    let active = state.active_worktree.lock().unwrap().clone();
+    match active {
+        Some(id) => Ok(worktree::load(&dir, &id)?.path),
+        None => Ok(dir),
+    }
+}
+
 // =============================================================================
 // Tauri commands
 // =============================================================================
@@ -171,37 +293,37 @@ fn get_project_dir(state: State<AppState>) -> Option<String> {
     state.project_dir.lock().unwrap().clone()
 }
 
+/// Switch the app to a project: reset per-project state and start watching it.
+/// A watcher that fails to start (e.g. OS watch limit reached) only costs
+/// live updates, so it doesn't fail project selection.
+fn open_project(app: &AppHandle, state: &AppState, path: String) {
+    // Drop the old watcher before starting a new one
+    *state.watcher.lock().unwrap() = None;
+    *state.watcher.lock().unwrap() = watcher::start(app, &path).ok();
+    *state.project_dir.lock().unwrap() = Some(path);
+    *state.active_worktree.lock().unwrap() = None;
+    *state.file_index.lock().unwrap() = None;
+    *state.code_stats.lock().unwrap() = None;
+}
+
 /// Set the current project directory
 #[tauri::command]
-fn set_project_dir(state: State<AppState>, path: String) -> Result<(), String> {
+fn set_project_dir(app: AppHandle, state: State<AppState>, path: String) -> Result<(), String> {
     let metadata = std::fs::metadata(&path).map_err(|e| e.to_string())?;
     if !metadata.is_dir() {
         return Err("Path is not a directory".to_string());
     }
-    *state.project_dir.lock().unwrap() = Some(path);
+    open_project(&app, &state, path);
     Ok(())
 }
 
 /// Create a new project directory
 #[tauri::command]
-fn create_project(state: State<AppState>, name: String) -> Result<String, String> {
+fn create_project(app: AppHandle, state: State<AppState>, name: String) -> Result<String, String> {
     let base = projects_base_dir()?;
     std::fs::create_dir_all(&base).map_err(|e| e.to_string())?;
 
-    // Sanitize name: lowercase, replace spaces with hyphens, remove special chars
-    let slug: String = name
-        .to_lowercase()
-        .chars()
-        .map(|c| {
-            if c.is_alphanumeric() || c == '-' {
-                c
-            } else if c == ' ' {
-                '-'
-            } else {
-                '_'
-            }
-        })
-        .collect();
+    let slug = slugify(&name);
     let slug = if slug.is_empty() {
         "my-project".to_string()
     } else {
@@ -226,16 +348,25 @@ fn create_project(state: State<AppState>, name: String) -> Result<String, String
 
     std::fs::create_dir_all(&final_path).map_err(|e| e.to_string())?;
     let path_str = final_path.to_string_lossy().to_string();
-    *state.project_dir.lock().unwrap() = Some(path_str.clone());
+    open_project(&app, &state, path_str.clone());
     Ok(path_str)
 }
 
-/// List files in the project directory
 /// Analyze project: detect language, framework, team status
 #[derive(Clone, Serialize)]
 struct ProjectAnalysis {
     languages: Vec<String>,
+    /// Languages with the evidence for each
+    detected_languages: Vec<detectors::Language>,
+    /// Most specific framework the project is confidently built on
     framework: Option<String>,
+    frameworks: Vec<detectors::Framework>,
+    package_managers: Vec<String>,
+    scripts: Vec<manifests::Script>,
+    /// Member packages as a tree, when the project is a workspace
+    workspace: Option<workspaces::WorkspaceNode>,
+    /// Test, lint, CI and container tooling with the commands to run it
+    tooling: tooling::Tooling,
     has_claude_config: bool,
     agent_count: usize,
     skill_count: usize,
@@ -245,81 +376,44 @@ struct ProjectAnalysis {
     suggestion: Option<String>,
 }
 
+/// Analysis walks the whole tree, so it runs off the main thread
 #[tauri::command]
-fn analyze_project(state: State<AppState>) -> Result<ProjectAnalysis, String> {
-    let dir = state
-        .project_dir
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("No project directory set")?;
+async fn analyze_project(state: State<'_, AppState>) -> Result<ProjectAnalysis, String> {
+    let dir = current_project_dir(&state)?;
+    tokio::task::spawn_blocking(move || analyze(&dir))
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    let root = Path::new(&dir);
-    let mut languages = Vec::new();
-    let mut framework = None;
-
-    // Detect languages and frameworks by config files
-    if root.join("package.json").exists() {
-        languages.push("JavaScript/TypeScript".to_string());
-        // Try to detect framework from package.json
-        if let Ok(content) = std::fs::read_to_string(root.join("package.json")) {
-            if content.contains("\"next\"") { framework = Some("Next.js".to_string()); }
-            else if content.contains("\"react\"") { framework = Some("React".to_string()); }
-            else if content.contains("\"vue\"") { framework = Some("Vue".to_string()); }
-            else if content.contains("\"svelte\"") { framework = Some("Svelte".to_string()); }
-            else if content.contains("\"express\"") { framework = Some("Express".to_string()); }
-            else if content.contains("\"nuxt\"") { framework = Some("Nuxt".to_string()); }
-            else if content.contains("\"angular\"") { framework = Some("Angular".to_string()); }
-        }
-    }
-    if root.join("Cargo.toml").exists() { languages.push("Rust".to_string()); }
-    if root.join("requirements.txt").exists() || root.join("pyproject.toml").exists() || root.join("setup.py").exists() {
-        languages.push("Python".to_string());
-        if let Ok(content) = std::fs::read_to_string(root.join("requirements.txt").as_path())
-            .or_else(|_| std::fs::read_to_string(root.join("pyproject.toml").as_path()))
-        {
-            if content.contains("django") { framework = Some("Django".to_string()); }
-            else if content.contains("flask") { framework = Some("Flask".to_string()); }
-            else if content.contains("fastapi") { framework = Some("FastAPI".to_string()); }
-        }
-    }
-    if root.join("go.mod").exists() { languages.push("Go".to_string()); }
-    if root.join("pom.xml").exists() || root.join("build.gradle").exists() {
-        languages.push("Java".to_string());
-        if root.join("pom.xml").exists() {
-            if let Ok(c) = std::fs::read_to_string(root.join("pom.xml")) {
-                if c.contains("spring") { framework = Some("Spring".to_string()); }
-            }
-        }
+fn analyze(dir: &str) -> ProjectAnalysis {
+    let root = Path::new(dir);
+    let found = manifests::read_dir(root, root);
+    let extensions = detectors::ExtensionCounts::scan(root);
+    let stack = detectors::detect(root, root, &found, &extensions);
+    let mut languages = stack.language_names();
+    if languages.is_empty() {
+        languages.push("Unknown".to_string());
     }
-    if root.join("Gemfile").exists() { languages.push("Ruby".to_string()); }
-    if root.join("Package.swift").exists() { languages.push("Swift".to_string()); }
-
-    if languages.is_empty() { languages.push("Unknown".to_string()); }
+    let framework = stack.frameworks.first().map(|f| f.name.clone());
+    let package_managers = manifests::package_managers(root, &found);
+    let scripts = found.iter().flat_map(|m| m.scripts.clone()).collect();
+    let workspace = workspaces::detect(root, &extensions);
+    let tooling = tooling::detect(root, &found);
 
     // Check .claude/ config
     let claude_dir = root.join(".claude");
     let has_claude_config = claude_dir.exists();
-    let agents_dir = claude_dir.join("agents");
-    let skills_dir = claude_dir.join("skills");
+    let project_root = scopes::ConfigRoot::project(dir);
 
-    let mut agents = Vec::new();
+    let agents: Vec<String> = agents::scan(&project_root)
+        .into_iter()
+        .map(|a| a.slug)
+        .collect();
     let mut skills = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(&agents_dir) {
-        for entry in entries.flatten() {
-            let name = entry.file_name().to_string_lossy().to_string();
-            if name.ends_with(".md") {
-                agents.push(name.trim_end_matches(".md").to_string());
-            }
-        }
-    }
-    if let Ok(entries) = std::fs::read_dir(&skills_dir) {
-        for entry in entries.flatten() {
-            let name = entry.file_name().to_string_lossy().to_string();
-            if name.ends_with(".md") {
-                skills.push(name.trim_end_matches(".md").to_string());
-            }
+    // Both `<name>/SKILL.md` and `<name>.md`; broken skills aren't counted
+    for skill in skills::scan(&project_root) {
+        if skill.is_valid() && !skills.contains(&skill.slug) {
+            skills.push(skill.slug);
         }
     }
 
@@ -335,9 +429,15 @@ fn analyze_project(state: State<AppState>) -> Result<ProjectAnalysis, String> {
         None
     };
 
-    Ok(ProjectAnalysis {
+    ProjectAnalysis {
         languages,
+        detected_languages: stack.languages,
         framework,
+        frameworks: stack.frameworks,
+        package_managers,
+        scripts,
+        workspace,
+        tooling,
         has_claude_config,
         agent_count: agents.len(),
         skill_count: skills.len(),
@@ -345,10 +445,11 @@ fn analyze_project(state: State<AppState>) -> Result<ProjectAnalysis, String> {
         skills,
         has_git,
         suggestion,
-    })
+    }
 }
 
-/// List files in the project directory
+/// List files in the project directory as a flat list of relative paths
+/// (up to four levels deep). Prefer `list_dir`, which loads lazily.
 #[tauri::command]
 fn list_project_files(state: State<AppState>) -> Result<Vec<String>, String> {
     let dir = state
@@ -358,177 +459,18 @@ fn list_project_files(state: State<AppState>) -> Result<Vec<String>, String> {
         .clone()
         .ok_or("No project directory set")?;
 
-    let mut files = Vec::new();
-    collect_files(Path::new(&dir), Path::new(&dir), &mut files, 0, 3)?;
+    let root = Path::new(&dir);
+    let files = walk::walker(root)
+        .max_depth(Some(4))
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .flatten()
+        .filter(|entry| entry.depth() > 0)
+        .map(|entry| walk::relative_path(root, entry.path()))
+        .collect();
     Ok(files)
 }
 
-fn collect_files(
-    base: &Path,
-    dir: &Path,
-    files: &mut Vec<String>,
-    depth: usize,
-    max_depth: usize,
-) -> Result<(), String> {
-    if depth > max_depth {
-        return Ok(());
-    }
-
-    let entries = std::fs::read_dir(dir).map_err(|e| e.to_string())?;
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        let name = path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-
-        // Skip heavy/internal dirs on all platforms
-        if name == ".git" || name == "node_modules" || name == "target" || name == ".next" || name == "dist" {
-            continue;
-        }
-
-        // Always use forward slashes for the frontend (even on Windows)
-        let relative = path
-            .strip_prefix(base)
-            .unwrap_or(&path)
-            .to_string_lossy()
-            .to_string()
-            .replace('\\', "/");
-        files.push(relative);
-
-        if path.is_dir() {
-            collect_files(base, &path, files, depth + 1, max_depth)?;
-        }
-    }
-    Ok(())
-}
-
-/// Agent metadata parsed from YAML frontmatter
-#[derive(Clone, Serialize)]
-struct AgentInfo {
-    slug: String,
-    name: String,
-    description: String,
-    model: String,
-}
-
-/// Skill metadata parsed from .md file
-#[derive(Clone, Serialize)]
-struct SkillInfo {
-    slug: String,
-    name: String,
-    description: String,
-}
-
-/// Parse YAML frontmatter from a markdown file.
-/// Returns a HashMap of key-value pairs from the --- delimited section.
-fn parse_frontmatter(content: &str) -> std::collections::HashMap<String, String> {
-    let mut map = std::collections::HashMap::new();
-    let trimmed = content.trim();
-    if !trimmed.starts_with("---") {
-        return map;
-    }
-    // Find the closing ---
-    if let Some(end) = trimmed[3..].find("---") {
-        let yaml_section = &trimmed[3..3 + end];
-        for line in yaml_section.lines() {
-            let line = line.trim();
-            if let Some(colon_pos) = line.find(':') {
-                let key = line[..colon_pos].trim().to_string();
-                let value = line[colon_pos + 1..].trim().trim_matches('"').to_string();
-                if !key.is_empty() && !value.is_empty() {
-                    map.insert(key, value);
-                }
-            }
-        }
-    }
-    map
-}
-
-/// Discover skills from .claude/skills/ with metadata
-#[tauri::command]
-fn discover_skills(state: State<AppState>) -> Result<Vec<SkillInfo>, String> {
-    let dir = state
-        .project_dir
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("No project directory set")?;
-
-    let skills_dir = Path::new(&dir).join(".claude").join("skills");
-    let mut skills = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(&skills_dir) {
-        for entry in entries.flatten() {
-            let file_name = entry.file_name().to_string_lossy().to_string();
-            if !file_name.ends_with(".md") {
-                continue;
-            }
-            let slug = file_name.trim_end_matches(".md").to_string();
-            let content = std::fs::read_to_string(entry.path()).unwrap_or_default();
-            let fm = parse_frontmatter(&content);
-
-            // Get first non-frontmatter line as description fallback
-            let body_desc = content
-                .split("---")
-                .nth(2)
-                .unwrap_or("")
-                .lines()
-                .find(|l| !l.trim().is_empty())
-                .unwrap_or("")
-                .trim()
-                .to_string();
-
-            skills.push(SkillInfo {
-                name: fm.get("name").cloned().unwrap_or_else(|| slug.clone()),
-                description: fm.get("description").cloned().unwrap_or(body_desc),
-                slug,
-            });
-        }
-    }
-
-    skills.sort_by(|a, b| a.slug.cmp(&b.slug));
-    Ok(skills)
-}
-
-/// Discover agents from .claude/agents/ with metadata
-#[tauri::command]
-fn discover_agents(state: State<AppState>) -> Result<Vec<AgentInfo>, String> {
-    let dir = state
-        .project_dir
-        .lock()
-        .unwrap()
-        .clone()
-        .ok_or("No project directory set")?;
-
-    let agents_dir = Path::new(&dir).join(".claude").join("agents");
-    let mut agents = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(&agents_dir) {
-        for entry in entries.flatten() {
-            let file_name = entry.file_name().to_string_lossy().to_string();
-            if !file_name.ends_with(".md") {
-                continue;
-            }
-            let slug = file_name.trim_end_matches(".md").to_string();
-            let content = std::fs::read_to_string(entry.path()).unwrap_or_default();
-            let fm = parse_frontmatter(&content);
-
-            agents.push(AgentInfo {
-                name: fm.get("name").cloned().unwrap_or_else(|| slug.clone()),
-                description: fm.get("description").cloned().unwrap_or_default(),
-                model: fm.get("model").cloned().unwrap_or_else(|| "sonnet".to_string()),
-                slug,
-            });
-        }
-    }
-
-    agents.sort_by(|a, b| a.slug.cmp(&b.slug));
-    Ok(agents)
-}
-
 // =============================================================================
 // Claude Code detection, installation, and auth
 // =============================================================================
@@ -729,10 +671,20 @@ struct ClaudeOutputEvent {
 
 #[derive(Clone, Serialize)]
 struct ClaudeDoneEvent {
+    run_id: Option<String>,
     success: bool,
     full_output: String,
 }
 
+/// Clears `current_run` when dropped, so no exit path leaves a run behind
+struct RunTracking<'a>(&'a AppState);
+
+impl Drop for RunTracking<'_> {
+    fn drop(&mut self) {
+        *self.0.current_run.lock().unwrap() = None;
+    }
+}
+
 /// Run a prompt through Claude Code CLI in print mode.
 /// Streams output via Tauri events.
 #[tauri::command]
@@ -741,6 +693,7 @@ async fn run_claude_prompt(
     state: State<'_, AppState>,
     prompt: String,
     auto_approve: Option<bool>,
+    isolate: Option<bool>,
 ) -> Result<(), String> {
     let dir = state
         .project_dir
@@ -749,6 +702,34 @@ async fn run_claude_prompt(
         .clone()
         .ok_or("No project directory set")?;
 
+    // Run inside the session worktree when isolation is on
+    let has_worktree = state.active_worktree.lock().unwrap().is_some();
+    if !has_worktree && isolate.unwrap_or(false) {
+        let info = worktree::create(&dir, None)?;
+        *state.active_worktree.lock().unwrap() = Some(info.id.clone());
+        app.emit("worktree-activated", info)
+            .map_err(|e| e.to_string())?;
+    }
+    let run_dir = active_run_dir(&state)?;
+
+    // Snapshot the run directory so everything this run does can be undone
+    // and reported. A failed checkpoint (e.g. git missing) doesn't block the run.
+    let run_id = format!("run-{}", now_millis());
+    let previous = state.run_reports.lock().unwrap().previous_snapshot(&run_dir);
+    let snapshot_dir = PathBuf::from(&run_dir);
+    let snapshot_run_id = run_id.clone();
+    let label = prompt.clone();
+    let (checkpoint, before) = tokio::task::spawn_blocking(move || {
+        let checkpoint = checkpoint::snapshot(&snapshot_dir, &snapshot_run_id, &label);
+        let before = changes::snapshot_tree(&snapshot_dir, previous.as_ref());
+        (checkpoint, before)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if let Ok(checkpoint) = checkpoint {
+        let _ = app.emit("checkpoint-created", checkpoint);
+    }
+
     // Resolve claude binary (GUI apps may not inherit shell PATH)
     let claude_bin = find_claude_binary()
         .ok_or("Claude Code를 찾을 수 없습니다. 설정에서 다시 설치해주세요.")?;
@@ -773,7 +754,7 @@ async fn run_claude_prompt(
     // Spawn claude in print mode with clean environment
     let mut child = tokio::process::Command::new(&claude_bin)
         .args(&args)
-        .current_dir(&dir)
+        .current_dir(&run_dir)
         .env_clear()
         .envs(&env_vars)
         .stdout(std::process::Stdio::piped())
@@ -790,10 +771,13 @@ async fn run_claude_prompt(
             }
         })?;
 
-    // Track child PID for stop_claude
+    // Track child PID for stop_claude, and the run for the watcher; the
+    // run is cleared however this function returns from here on
     if let Some(pid) = child.id() {
         *state.child_pid.lock().unwrap() = Some(pid);
     }
+    *state.current_run.lock().unwrap() = Some(run_id.clone());
+    let _tracking = RunTracking(&state);
 
     let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
     let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
@@ -823,9 +807,23 @@ async fn run_claude_prompt(
     let status = child.wait().await.map_err(|e| e.to_string())?;
     let stderr_output = stderr_handle.await.unwrap_or_default();
 
-    // Clear PID tracking
+    // Clear the PID; `_tracking` clears the run when the command returns
     *state.child_pid.lock().unwrap() = None;
 
+    // Report what the run changed on disk
+    let report_dir = PathBuf::from(&run_dir);
+    let report_run_id = run_id.clone();
+    let (report, after) = tokio::task::spawn_blocking(move || {
+        let after = changes::snapshot_tree(&report_dir, Some(&before));
+        let report = changes::build_report(&report_dir, &report_run_id, &before, &after);
+        (report, after)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let files_changed = state.run_reports.lock().unwrap().push(report, after);
+    app.emit("files-changed", files_changed)
+        .map_err(|e| e.to_string())?;
+
     // If claude failed and stdout is empty, use stderr as error message
     // Translate common errors to user-friendly messages
     let final_output = if !status.success() && full_output.trim().is_empty() {
@@ -848,6 +846,7 @@ async fn run_claude_prompt(
     app.emit(
         "claude-done",
         ClaudeDoneEvent {
+            run_id: Some(run_id),
             success: status.success(),
             full_output: final_output,
         },
@@ -861,6 +860,7 @@ async fn run_claude_prompt(
 #[tauri::command]
 async fn stop_claude(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
     let pid = state.child_pid.lock().unwrap().take();
+    let run_id = state.current_run.lock().unwrap().clone();
     if let Some(pid) = pid {
         #[cfg(not(target_os = "windows"))]
         {
@@ -879,6 +879,7 @@ async fn stop_claude(app: AppHandle, state: State<'_, AppState>) -> Result<(), S
         app.emit(
             "claude-done",
             ClaudeDoneEvent {
+                run_id,
                 success: false,
                 full_output: "Stopped by user.".to_string(),
             },
@@ -902,6 +903,14 @@ pub fn run() {
         .manage(AppState {
             project_dir: Mutex::new(None),
             child_pid: Mutex::new(None),
+            active_worktree: Mutex::new(None),
+            current_run: Mutex::new(None),
+            run_reports: Mutex::new(changes::RunReports::default()),
+            watcher: Mutex::new(None),
+            file_index: Mutex::new(None),
+            active_search: Mutex::new(None),
+            code_stats: Mutex::new(None),
+            memory_overview: Mutex::new(None),
         })
         .invoke_handler(tauri::generate_handler![
             check_claude_code,
@@ -913,10 +922,59 @@ pub fn run() {
             create_project,
             analyze_project,
             list_project_files,
-            discover_skills,
-            discover_agents,
+            files::list_dir,
+            files::read_project_file,
+            safe_write::write_project_file,
+            finder::find_files,
+            search::search_content,
+            search::cancel_search,
+            skills::discover_skills,
+            slash_commands::discover_commands,
+            slash_commands::expand_command,
+            agents::discover_agents,
+            config_editor::list_role_templates,
+            config_editor::create_agent,
+            config_editor::update_agent,
+            config_editor::rename_agent,
+            config_editor::delete_agent,
+            config_editor::create_skill,
+            config_editor::update_skill,
+            config_editor::rename_skill,
+            config_editor::delete_skill,
+            lint::lint_claude_config,
+            hooks::list_hooks,
+            hooks::add_hook,
+            hooks::update_hook,
+            hooks::remove_hook,
+            hooks::test_hook,
+            permissions::get_permissions,
+            permissions::validate_permission_rule,
+            permissions::add_permission_rule,
+            permissions::remove_permission_rule,
+            mcp::list_mcp_servers,
+            mcp::add_mcp_server,
+            mcp::update_mcp_server,
+            mcp::remove_mcp_server,
+            mcp::probe_mcp_server,
+            memory::list_memory_files,
+            memory::read_memory_file,
+            memory::write_memory_file,
+            claude_md::draft_claude_md,
+            detectors::list_detectors,
+            code_stats::get_code_stats,
             run_claude_prompt,
             stop_claude,
+            worktree::create_worktree,
+            worktree::list_worktrees,
+            worktree::set_active_worktree,
+            worktree::diff_worktree,
+            worktree::merge_worktree,
+            worktree::discard_worktree,
+            checkpoint::list_checkpoints,
+            checkpoint::diff_checkpoint,
+            checkpoint::restore_checkpoint,
+            changes::get_run_changes,
+            changes::get_file_diff,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,329 @@
+//! Checks for agent and skill definitions.
+//!
+//! Claude Code skips or silently reinterprets definitions it can't use: a
+//! typo in `model` or `tools`, a missing description, two agents with the
+//! same name. `lint_claude_config` reports these up front, pointing at the
+//! offending line where there is one. Plugin items are left alone; they
+//! aren't the user's to fix.
+
+use serde::Serialize;
+use std::path::Path;
+use tauri::State;
+
+use crate::agents::{self, AgentInfo};
+use crate::config_editor::validate_slug;
+use crate::frontmatter::{self, ParseError};
+use crate::scopes;
+use crate::skills::{self, SkillInfo, SKILL_FILE};
+use crate::{current_project_dir, AppState};
+
+/// Descriptions longer than this are cut off in Claude's context
+const MAX_DESCRIPTION_CHARS: usize = 1024;
+
+/// Model aliases Claude Code accepts besides full `claude-…` model ids
+const MODEL_ALIASES: &[&str] = &["inherit", "sonnet", "opus", "haiku", "opusplan"];
+
+/// Built-in tool names. MCP tools (`mcp__server__tool`) are always allowed.
+pub(crate) const KNOWN_TOOLS: &[&str] = &[
+    "Agent",
+    "AskUserQuestion",
+    "Bash",
+    "BashOutput",
+    "Edit",
+    "ExitPlanMode",
+    "Glob",
+    "Grep",
+    "KillShell",
+    "LS",
+    "MultiEdit",
+    "NotebookEdit",
+    "NotebookRead",
+    "Read",
+    "SlashCommand",
+    "Skill",
+    "Task",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
+
+/// One finding
+#[derive(Clone, Serialize)]
+pub struct Diagnostic {
+    /// "error" (Claude won't load it as intended), "warning" or "info"
+    pub severity: String,
+    /// Stable identifier, e.g. `unknown-model`
+    pub code: String,
+    pub message: String,
+    /// "agent" or "skill"
+    pub kind: String,
+    pub slug: String,
+    pub scope: String,
+    /// Absolute path of the file
+    pub path: String,
+    /// 1-based line, when the problem is on a specific line
+    pub line: Option<usize>,
+}
+
+/// Collects diagnostics for one item
+struct Linter<'a> {
+    kind: &'static str,
+    slug: &'a str,
+    scope: &'a str,
+    path: &'a str,
+    content: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    fn report(&mut self, severity: &str, code: &str, message: String, line: Option<usize>) {
+        self.diagnostics.push(Diagnostic {
+            severity: severity.to_string(),
+            code: code.to_string(),
+            message,
+            kind: self.kind.to_string(),
+            slug: self.slug.to_string(),
+            scope: self.scope.to_string(),
+            path: self.path.to_string(),
+            line,
+        });
+    }
+
+    /// Line of `key`, or of the frontmatter's opening fence without one
+    fn line_of(&self, key: &str) -> Option<usize> {
+        frontmatter::key_line(self.content, key).or_else(|| frontmatter::start_line(self.content))
+    }
+
+    fn parse_error(&mut self, error: &Option<ParseError>) -> bool {
+        match error {
+            Some(error) => {
+                self.report(
+                    "error",
+                    "invalid-frontmatter",
+                    format!("Frontmatter can't be parsed: {}", error.message),
+                    error.line,
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn required(&mut self, key: &str, present: bool) {
+        if !present {
+            let line = frontmatter::start_line(self.content).or(Some(1));
+            self.report(
+                "error",
+                &format!("missing-{}", key),
+                format!("Frontmatter has no `{}`", key),
+                line,
+            );
+        }
+    }
+
+    fn description(&mut self, description: &str) {
+        let length = description.chars().count();
+        if length > MAX_DESCRIPTION_CHARS {
+            self.report(
+                "warning",
+                "long-description",
+                format!(
+                    "Description is {} characters; keep it under {} so it isn't cut off",
+                    length, MAX_DESCRIPTION_CHARS
+                ),
+                self.line_of("description"),
+            );
+        }
+    }
+
+    fn model(&mut self, model: Option<&str>) {
+        let Some(model) = model else {
+            return;
+        };
+        if !MODEL_ALIASES.contains(&model) && !model.starts_with("claude-") {
+            self.report(
+                "error",
+                "unknown-model",
+                format!(
+                    "Unknown model '{}'; use one of {} or a full model id",
+                    model,
+                    MODEL_ALIASES.join(", ")
+                ),
+                self.line_of("model"),
+            );
+        }
+    }
+
+    fn tools(&mut self, key: &str, tools: Option<&Vec<String>>) {
+        for tool in tools.into_iter().flatten() {
+            // `Bash(git diff:*)` restricts a tool; the name is what's before `(`
+            let name = tool.split('(').next().unwrap_or(tool).trim();
+            if name == "*" || name.starts_with("mcp__") || KNOWN_TOOLS.contains(&name) {
+                continue;
+            }
+            let suggestion = KNOWN_TOOLS
+                .iter()
+                .find(|known| known.eq_ignore_ascii_case(name))
+                .map(|known| format!(" (did you mean '{}'?)", known))
+                .unwrap_or_default();
+            self.report(
+                "warning",
+                "unknown-tool",
+                format!("Unknown tool '{}' in `{}`{}", name, key, suggestion),
+                self.line_of(key),
+            );
+        }
+    }
+
+    fn name_matches(&mut self, name: &str, expected: &str, what: &str) {
+        if let Err(e) = validate_slug(name) {
+            self.report("warning", "invalid-name", e, self.line_of("name"));
+        } else if name != expected {
+            self.report(
+                "warning",
+                "name-mismatch",
+                format!("Name '{}' doesn't match the {} '{}'", name, what, expected),
+                self.line_of("name"),
+            );
+        }
+    }
+}
+
+fn lint_agent(agent: &AgentInfo) -> Vec<Diagnostic> {
+    let content = std::fs::read_to_string(&agent.origin.source_path).unwrap_or_default();
+    let mut lint = Linter {
+        kind: "agent",
+        slug: &agent.slug,
+        scope: &agent.origin.scope,
+        path: &agent.origin.source_path,
+        content: &content,
+        diagnostics: Vec::new(),
+    };
+    if lint.parse_error(&agent.error) {
+        return lint.diagnostics;
+    }
+    let (fm, body, _) = frontmatter::parse::<frontmatter::AgentFrontmatter>(&content);
+    lint.required("name", fm.name.is_some());
+    lint.required("description", fm.description.is_some());
+    if let Some(name) = &fm.name {
+        lint.name_matches(name, &agent.slug, "file name");
+    }
+    lint.description(fm.description.as_deref().unwrap_or(""));
+    lint.model(fm.model.as_deref());
+    lint.tools("tools", fm.tools.as_ref());
+    if body.trim().is_empty() {
+        lint.report(
+            "warning",
+            "empty-prompt",
+            "Agent has no system prompt after the frontmatter".to_string(),
+            None,
+        );
+    }
+    lint.diagnostics
+}
+
+/// Bundled files that `SKILL.md` never mentions, so Claude won't know to
+/// open them
+fn unreferenced_files<'s>(skill: &'s SkillInfo, body: &str) -> Vec<&'s String> {
+    skill
+        .files
+        .iter()
+        .filter(|file| {
+            let name = Path::new(file.as_str())
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            !body.contains(file.as_str()) && !body.contains(&name)
+        })
+        .collect()
+}
+
+fn lint_skill(skill: &SkillInfo) -> Vec<Diagnostic> {
+    let content = std::fs::read_to_string(&skill.origin.source_path).unwrap_or_default();
+    let mut lint = Linter {
+        kind: "skill",
+        slug: &skill.slug,
+        scope: &skill.origin.scope,
+        path: &skill.origin.source_path,
+        content: &content,
+        diagnostics: Vec::new(),
+    };
+    for problem in &skill.problems {
+        // Missing keys are reported below, on their own
+        if !problem.contains("frontmatter is missing") {
+            lint.report("error", "invalid-layout", problem.clone(), None);
+        }
+    }
+    if lint.parse_error(&skill.error) || content.is_empty() {
+        return lint.diagnostics;
+    }
+    let (fm, body, _) = frontmatter::parse::<frontmatter::SkillFrontmatter>(&content);
+    if skill.layout == "directory" {
+        lint.required("name", fm.name.is_some());
+        lint.required("description", fm.description.is_some());
+    }
+    if let Some(name) = &fm.name {
+        let what = if skill.layout == "directory" {
+            "directory name"
+        } else {
+            "file name"
+        };
+        lint.name_matches(name, &skill.slug, what);
+    }
+    lint.description(&skill.description);
+    lint.model(fm.model.as_deref());
+    lint.tools("allowed-tools", fm.allowed_tools.as_ref());
+    for file in unreferenced_files(skill, body) {
+        lint.report(
+            "info",
+            "unreferenced-file",
+            format!("{} is bundled but never mentioned in {}", file, SKILL_FILE),
+            None,
+        );
+    }
+    lint.diagnostics
+}
+
+fn shadowed(kind: &str, slug: &str, origin: &scopes::Origin) -> Option<Diagnostic> {
+    let winner = origin.shadowed_by.as_ref()?;
+    Some(Diagnostic {
+        severity: "warning".to_string(),
+        code: "duplicate-name".to_string(),
+        message: format!(
+            "'{}' is also defined in {}, which takes precedence",
+            slug, winner
+        ),
+        kind: kind.to_string(),
+        slug: slug.to_string(),
+        scope: origin.scope.clone(),
+        path: origin.source_path.clone(),
+        line: None,
+    })
+}
+
+/// Lint project and user agents and skills
+#[tauri::command]
+pub fn lint_claude_config(state: State<AppState>) -> Result<Vec<Diagnostic>, String> {
+    let dir = current_project_dir(&state)?;
+    let roots = scopes::config_roots(&dir);
+
+    let mut agent_list: Vec<AgentInfo> = roots.iter().flat_map(agents::scan).collect();
+    scopes::mark_shadowed(&mut agent_list, |a| a.name.clone(), |a| &mut a.origin);
+    let mut skill_list: Vec<SkillInfo> = roots.iter().flat_map(skills::scan).collect();
+    scopes::mark_shadowed(&mut skill_list, |s| s.slug.clone(), |s| &mut s.origin);
+
+    let mut diagnostics = Vec::new();
+    for agent in agent_list.iter().filter(|a| a.origin.scope != "plugin") {
+        diagnostics.extend(lint_agent(agent));
+        diagnostics.extend(shadowed("agent", &agent.name, &agent.origin));
+    }
+    for skill in skill_list.iter().filter(|s| s.origin.scope != "plugin") {
+        diagnostics.extend(lint_skill(skill));
+        // Same-scope file/directory clashes are already a layout error
+        if skill.layout == "directory" || skill.problems.is_empty() {
+            diagnostics.extend(shadowed("skill", &skill.slug, &skill.origin));
+        }
+    }
+    Ok(diagnostics)
+}
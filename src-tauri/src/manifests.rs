@@ -0,0 +1,668 @@
+//! Project manifests: package.json, pyproject.toml, requirements.txt,
+//! Cargo.toml, go.mod and pom.xml.
+//!
+//! Each manifest is parsed properly rather than searched for substrings, so
+//! a framework named in a description doesn't count and dev-only
+//! dependencies are told apart from runtime ones. `detectors` matches
+//! frameworks against the declared dependencies.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::walk;
+
+/// A declared dependency
+#[derive(Clone, Serialize)]
+pub struct Dependency {
+    pub name: String,
+    /// Version requirement as written, e.g. `^18.2.0`
+    pub version: Option<String>,
+    /// "runtime", "dev", "peer", "optional", "build" or "indirect"
+    pub kind: String,
+}
+
+/// A script or task defined in a manifest
+#[derive(Clone, Serialize)]
+pub struct Script {
+    pub name: String,
+    pub command: String,
+    /// Manifest it comes from, relative to the project root
+    pub source: String,
+}
+
+/// One parsed manifest
+#[derive(Clone, Serialize)]
+pub struct Manifest {
+    /// Relative to the project root
+    pub path: String,
+    /// "node", "python", "rust", "go" or "java"
+    pub ecosystem: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub dependencies: Vec<Dependency>,
+    pub scripts: Vec<Script>,
+    /// Why the file couldn't be parsed, if it couldn't
+    pub error: Option<String>,
+}
+
+fn dependency(name: &str, version: Option<String>, kind: &str) -> Dependency {
+    Dependency {
+        name: name.to_string(),
+        version: version.filter(|v| !v.is_empty()),
+        kind: kind.to_string(),
+    }
+}
+
+fn manifest(root: &Path, path: &Path, ecosystem: &str) -> Manifest {
+    Manifest {
+        path: walk::relative_path(root, path),
+        ecosystem: ecosystem.to_string(),
+        name: None,
+        version: None,
+        description: None,
+        dependencies: Vec::new(),
+        scripts: Vec::new(),
+        error: None,
+    }
+}
+
+fn package_json(content: &str, manifest: &mut Manifest) -> Result<(), String> {
+    let package: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let text = |key: &str| package.get(key).and_then(Value::as_str).map(str::to_string);
+    manifest.name = text("name");
+    manifest.version = text("version");
+    manifest.description = text("description");
+    for (key, kind) in [
+        ("dependencies", "runtime"),
+        ("devDependencies", "dev"),
+        ("peerDependencies", "peer"),
+        ("optionalDependencies", "optional"),
+    ] {
+        let deps = package.get(key).and_then(Value::as_object);
+        for (name, version) in deps.into_iter().flatten() {
+            let version = version.as_str().map(str::to_string);
+            manifest.dependencies.push(dependency(name, version, kind));
+        }
+    }
+    let scripts = package.get("scripts").and_then(Value::as_object);
+    for (name, command) in scripts.into_iter().flatten() {
+        manifest.scripts.push(Script {
+            name: name.clone(),
+            command: command.as_str().unwrap_or_default().to_string(),
+            source: manifest.path.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Python package names compare case-insensitively with `-`, `_` and `.`
+/// all equal
+fn normalize_python_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+/// Split a PEP 508 requirement like `django[argon2]>=4.2; python_version>"3.8"`
+fn pep508(requirement: &str) -> Option<(String, Option<String>)> {
+    let requirement = requirement.split(';').next()?.trim();
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-_.".contains(c)))
+        .unwrap_or(requirement.len());
+    let name = &requirement[..end];
+    if name.is_empty() {
+        return None;
+    }
+    let mut rest = requirement[end..].trim();
+    if rest.starts_with('[') {
+        rest = rest.split_once(']').map(|(_, r)| r.trim()).unwrap_or("");
+    }
+    let version = rest.trim_start_matches('(').trim_end_matches(')').trim();
+    Some((
+        normalize_python_name(name),
+        (!version.is_empty()).then(|| version.to_string()),
+    ))
+}
+
+/// Version of a TOML dependency written as `"1.0"` or `{ version = "1.0" }`
+fn toml_version(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(version) => Some(version.clone()),
+        toml::Value::Table(table) => table
+            .get("version")
+            .and_then(toml::Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+fn toml_table<'a>(table: &'a toml::Table, path: &[&str]) -> Option<&'a toml::Table> {
+    let mut current = table;
+    for key in path {
+        current = current.get(*key)?.as_table()?;
+    }
+    Some(current)
+}
+
+fn pyproject(content: &str, manifest: &mut Manifest) -> Result<(), String> {
+    let doc: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut add_requirements = |list: Option<&toml::Value>, kind: &str| {
+        let items = list.and_then(toml::Value::as_array);
+        for item in items.into_iter().flatten().filter_map(toml::Value::as_str) {
+            if let Some((name, version)) = pep508(item) {
+                manifest.dependencies.push(dependency(&name, version, kind));
+            }
+        }
+    };
+
+    let project = toml_table(&doc, &["project"]);
+    if let Some(project) = project {
+        add_requirements(project.get("dependencies"), "runtime");
+        for (_, group) in toml_table(project, &["optional-dependencies"])
+            .into_iter()
+            .flatten()
+        {
+            add_requirements(Some(group), "optional");
+        }
+    }
+    // PEP 735 dependency groups are for development
+    for (_, group) in toml_table(&doc, &["dependency-groups"])
+        .into_iter()
+        .flatten()
+    {
+        add_requirements(Some(group), "dev");
+    }
+
+    let poetry = toml_table(&doc, &["tool", "poetry"]);
+    if let Some(poetry) = poetry {
+        let mut add_table = |table: Option<&toml::Table>, kind: &str| {
+            for (name, value) in table.into_iter().flatten() {
+                if name != "python" {
+                    let name = normalize_python_name(name);
+                    manifest
+                        .dependencies
+                        .push(dependency(&name, toml_version(value), kind));
+                }
+            }
+        };
+        add_table(toml_table(poetry, &["dependencies"]), "runtime");
+        add_table(toml_table(poetry, &["dev-dependencies"]), "dev");
+        for (_, group) in toml_table(poetry, &["group"]).into_iter().flatten() {
+            let deps = group
+                .as_table()
+                .and_then(|g| toml_table(g, &["dependencies"]));
+            add_table(deps, "dev");
+        }
+    }
+
+    let text = |table: Option<&toml::Table>, key: &str| {
+        table
+            .and_then(|t| t.get(key))
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+    };
+    manifest.name = text(project, "name").or_else(|| text(poetry, "name"));
+    manifest.version = text(project, "version").or_else(|| text(poetry, "version"));
+    manifest.description = text(project, "description").or_else(|| text(poetry, "description"));
+
+    // Console entry points, and task runners' tasks
+    let scripts = [
+        toml_table(&doc, &["project", "scripts"]),
+        toml_table(&doc, &["tool", "poetry", "scripts"]),
+        toml_table(&doc, &["tool", "pdm", "scripts"]),
+        toml_table(&doc, &["tool", "poe", "tasks"]),
+    ];
+    for table in scripts.into_iter().flatten() {
+        for (name, value) in table {
+            let command = match value {
+                toml::Value::String(command) => command.clone(),
+                toml::Value::Table(task) => ["cmd", "shell", "script"]
+                    .iter()
+                    .find_map(|key| task.get(*key).and_then(toml::Value::as_str))
+                    .unwrap_or_default()
+                    .to_string(),
+                _ => continue,
+            };
+            manifest.scripts.push(Script {
+                name: name.clone(),
+                command,
+                source: manifest.path.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn requirements(content: &str, kind: &str, manifest: &mut Manifest) {
+    for line in content.lines() {
+        let line = line.split(" #").next().unwrap_or("").trim();
+        // Options (`-r other.txt`, `-e .`), comments and URLs aren't packages
+        if line.is_empty() || line.starts_with(['#', '-']) || line.contains("://") {
+            continue;
+        }
+        if let Some((name, version)) = pep508(line) {
+            manifest.dependencies.push(dependency(&name, version, kind));
+        }
+    }
+}
+
+fn cargo_toml(content: &str, manifest: &mut Manifest) -> Result<(), String> {
+    let doc: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let package = toml_table(&doc, &["package"]);
+    let text = |key: &str| {
+        package
+            .and_then(|p| p.get(key))
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+    };
+    manifest.name = text("name");
+    manifest.version = text("version");
+    manifest.description = text("description");
+
+    // `[workspace.dependencies]` is left out: like Maven's
+    // `<dependencyManagement>` it only pins versions for member crates
+    let mut tables: Vec<(&toml::Table, &str)> = Vec::new();
+    let sections = [
+        ("dependencies", "runtime"),
+        ("dev-dependencies", "dev"),
+        ("build-dependencies", "build"),
+    ];
+    for (key, kind) in sections {
+        if let Some(table) = toml_table(&doc, &[key]) {
+            tables.push((table, kind));
+        }
+        // `[target.'cfg(…)'.dependencies]`
+        for (_, target) in toml_table(&doc, &["target"]).into_iter().flatten() {
+            if let Some(table) = target.as_table().and_then(|t| toml_table(t, &[key])) {
+                tables.push((table, kind));
+            }
+        }
+    }
+    for (table, kind) in tables {
+        for (name, value) in table {
+            // `foo = { package = "bar" }` depends on bar
+            let package = value
+                .get("package")
+                .and_then(toml::Value::as_str)
+                .unwrap_or(name);
+            manifest
+                .dependencies
+                .push(dependency(package, toml_version(value), kind));
+        }
+    }
+    Ok(())
+}
+
+fn go_mod(content: &str, manifest: &mut Manifest) {
+    let mut in_require = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(module) = line.strip_prefix("module ") {
+            manifest.name = Some(module.trim().to_string());
+            continue;
+        }
+        if let Some(version) = line.strip_prefix("go ") {
+            manifest.version = Some(version.trim().to_string());
+            continue;
+        }
+        let requirement = if in_require {
+            if line == ")" {
+                in_require = false;
+                continue;
+            }
+            line
+        } else if line == "require (" {
+            in_require = true;
+            continue;
+        } else if let Some(single) = line.strip_prefix("require ") {
+            single
+        } else {
+            continue;
+        };
+        let (requirement, comment) = requirement.split_once("//").unwrap_or((requirement, ""));
+        let mut parts = requirement.split_whitespace();
+        if let Some(path) = parts.next() {
+            let kind = if comment.trim() == "indirect" {
+                "indirect"
+            } else {
+                "runtime"
+            };
+            let version = parts.next().map(str::to_string);
+            manifest.dependencies.push(dependency(path, version, kind));
+        }
+    }
+}
+
+fn pom_xml(content: &str, manifest: &mut Manifest) -> Result<(), String> {
+    let doc = roxmltree::Document::parse(content).map_err(|e| e.to_string())?;
+    let project = doc.root_element();
+    let child = |node: roxmltree::Node, name: &str| {
+        node.children()
+            .find(|c| c.has_tag_name(name))
+            .and_then(|c| c.text())
+            .map(|t| t.trim().to_string())
+    };
+
+    // `${spring.version}` placeholders come from <properties> and the parent
+    let mut properties: HashMap<String, String> = HashMap::new();
+    if let Some(props) = project.children().find(|c| c.has_tag_name("properties")) {
+        for prop in props.children().filter(|c| c.is_element()) {
+            if let Some(text) = prop.text() {
+                properties.insert(prop.tag_name().name().to_string(), text.trim().to_string());
+            }
+        }
+    }
+    let parent = project.children().find(|c| c.has_tag_name("parent"));
+    if let Some(version) = parent.and_then(|p| child(p, "version")) {
+        properties.insert("project.parent.version".to_string(), version);
+    }
+    let resolve = |value: Option<String>| {
+        value.map(
+            |v| match v.strip_prefix("${").and_then(|r| r.strip_suffix('}')) {
+                Some(key) => properties.get(key).cloned().unwrap_or(v),
+                None => v,
+            },
+        )
+    };
+
+    manifest.name = child(project, "artifactId");
+    manifest.version = resolve(child(project, "version"));
+    manifest.description = child(project, "description");
+
+    // The parent POM (e.g. spring-boot-starter-parent) is a dependency too
+    if let Some(parent) = parent {
+        if let (Some(group), Some(artifact)) =
+            (child(parent, "groupId"), child(parent, "artifactId"))
+        {
+            let name = format!("{}:{}", group, artifact);
+            let version = child(parent, "version");
+            manifest
+                .dependencies
+                .push(dependency(&name, version, "runtime"));
+        }
+    }
+    // Only the project's own `<dependencies>`: `<dependencyManagement>` only
+    // pins versions, and plugin and profile dependencies aren't the project's
+    let dependencies = project
+        .children()
+        .filter(|c| c.has_tag_name("dependencies"))
+        .flat_map(|deps| deps.children())
+        .filter(|n| n.has_tag_name("dependency"));
+    for dep in dependencies {
+        let (Some(group), Some(artifact)) = (child(dep, "groupId"), child(dep, "artifactId"))
+        else {
+            continue;
+        };
+        let kind = match child(dep, "scope").as_deref() {
+            Some("test") => "dev",
+            Some("provided") => "build",
+            _ if child(dep, "optional").as_deref() == Some("true") => "optional",
+            _ => "runtime",
+        };
+        let name = format!("{}:{}", group, artifact);
+        manifest
+            .dependencies
+            .push(dependency(&name, resolve(child(dep, "version")), kind));
+    }
+    Ok(())
+}
+
+/// Parse the manifests that sit directly in `dir`. Paths are reported
+/// relative to `root`.
+pub(crate) fn read_dir(root: &Path, dir: &Path) -> Vec<Manifest> {
+    let mut manifests = Vec::new();
+    let mut parse =
+        |name: &str,
+         ecosystem: &str,
+         parser: &dyn Fn(&str, &mut Manifest) -> Result<(), String>| {
+            let path = dir.join(name);
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                return;
+            };
+            let mut parsed = manifest(root, &path, ecosystem);
+            parsed.error = parser(&content, &mut parsed).err();
+            manifests.push(parsed);
+        };
+    parse("package.json", "node", &package_json);
+    parse("pyproject.toml", "python", &pyproject);
+    parse("requirements.txt", "python", &|c, m| {
+        requirements(c, "runtime", m);
+        Ok(())
+    });
+    parse("requirements-dev.txt", "python", &|c, m| {
+        requirements(c, "dev", m);
+        Ok(())
+    });
+    parse("Cargo.toml", "rust", &cargo_toml);
+    parse("go.mod", "go", &|c, m| {
+        go_mod(c, m);
+        Ok(())
+    });
+    parse("pom.xml", "java", &pom_xml);
+    manifests
+}
+
+/// Node package manager: the `packageManager` field, then the lockfile
+pub(crate) fn node_package_manager(dir: &Path) -> &'static str {
+    let declared = std::fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<Value>(&c).ok())
+        .and_then(|p| p.get("packageManager")?.as_str().map(str::to_string));
+    for manager in ["pnpm", "yarn", "bun", "npm"] {
+        if declared
+            .as_deref()
+            .is_some_and(|d| d.starts_with(&format!("{}@", manager)))
+        {
+            return manager;
+        }
+    }
+    if dir.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if dir.join("yarn.lock").exists() {
+        "yarn"
+    } else if dir.join("bun.lockb").exists() || dir.join("bun.lock").exists() {
+        "bun"
+    } else {
+        "npm"
+    }
+}
+
+/// How the package manager runs a package.json script
+pub(crate) fn run_script(manager: &str, script: &str) -> String {
+    match (manager, script) {
+        ("npm", "test" | "start") => format!("npm {}", script),
+        ("npm" | "bun", _) => format!("{} run {}", manager, script),
+        _ => format!("{} {}", manager, script),
+    }
+}
+
+/// Python package manager, from lockfiles and `[tool.*]` tables
+fn python_package_manager(dir: &Path) -> &'static str {
+    let pyproject = std::fs::read_to_string(dir.join("pyproject.toml")).unwrap_or_default();
+    let has_tool = |tool: &str| {
+        pyproject
+            .lines()
+            .any(|l| l.trim().starts_with(&format!("[tool.{}", tool)))
+    };
+    if dir.join("uv.lock").exists() || has_tool("uv") {
+        "uv"
+    } else if dir.join("poetry.lock").exists() || has_tool("poetry") {
+        "poetry"
+    } else if dir.join("pdm.lock").exists() || has_tool("pdm") {
+        "pdm"
+    } else if dir.join("Pipfile").exists() {
+        "pipenv"
+    } else {
+        "pip"
+    }
+}
+
+/// Package managers for the manifests in `dir`
+pub(crate) fn package_managers(dir: &Path, manifests: &[Manifest]) -> Vec<String> {
+    let mut managers: Vec<String> = Vec::new();
+    for manifest in manifests {
+        let manager = match manifest.ecosystem.as_str() {
+            "node" => node_package_manager(dir),
+            "python" => python_package_manager(dir),
+            "rust" => "cargo",
+            "go" => "go",
+            _ => "maven",
+        };
+        if !managers.iter().any(|m| m == manager) {
+            managers.push(manager.to_string());
+        }
+    }
+    managers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty(ecosystem: &str) -> Manifest {
+        manifest(Path::new("/p"), Path::new("/p/file"), ecosystem)
+    }
+
+    fn deps(manifest: &Manifest) -> Vec<(String, Option<String>, String)> {
+        manifest
+            .dependencies
+            .iter()
+            .map(|d| (d.name.clone(), d.version.clone(), d.kind.clone()))
+            .collect()
+    }
+
+    fn dep(name: &str, version: Option<&str>, kind: &str) -> (String, Option<String>, String) {
+        (
+            name.to_string(),
+            version.map(str::to_string),
+            kind.to_string(),
+        )
+    }
+
+    #[test]
+    fn pep508_splits_name_and_version() {
+        assert_eq!(
+            pep508(r#"Django[argon2] >=4.2; python_version > "3.8""#),
+            Some(("django".to_string(), Some(">=4.2".to_string())))
+        );
+        assert_eq!(
+            pep508("zope.interface (>=5)"),
+            Some(("zope-interface".to_string(), Some(">=5".to_string())))
+        );
+        assert_eq!(pep508("requests"), Some(("requests".to_string(), None)));
+        assert_eq!(pep508(" ; os_name == 'nt'"), None);
+    }
+
+    #[test]
+    fn go_mod_reads_module_and_requirements() {
+        let mut manifest = empty("go");
+        go_mod(
+            "module example.com/app\n\ngo 1.22\n\nrequire (\n\tgithub.com/gin-gonic/gin v1.9.1\n\tgolang.org/x/text v0.14.0 // indirect\n)\nrequire github.com/go-chi/chi/v5 v5.0.0\n",
+            &mut manifest,
+        );
+        assert_eq!(manifest.name.as_deref(), Some("example.com/app"));
+        assert_eq!(manifest.version.as_deref(), Some("1.22"));
+        assert_eq!(
+            deps(&manifest),
+            vec![
+                dep("github.com/gin-gonic/gin", Some("v1.9.1"), "runtime"),
+                dep("golang.org/x/text", Some("v0.14.0"), "indirect"),
+                dep("github.com/go-chi/chi/v5", Some("v5.0.0"), "runtime"),
+            ]
+        );
+    }
+
+    #[test]
+    fn cargo_toml_skips_workspace_version_pins() {
+        let mut manifest = empty("rust");
+        cargo_toml(
+            r#"
+[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+axum = "0.7"
+
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+web = { package = "actix-web", version = "4" }
+
+[dev-dependencies]
+insta = "1"
+
+[target.'cfg(unix)'.build-dependencies]
+cc = "1"
+"#,
+            &mut manifest,
+        )
+        .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("app"));
+        assert_eq!(
+            deps(&manifest),
+            vec![
+                dep("serde", Some("1"), "runtime"),
+                dep("actix-web", Some("4"), "runtime"),
+                dep("insta", Some("1"), "dev"),
+                dep("cc", Some("1"), "build"),
+            ]
+        );
+    }
+
+    #[test]
+    fn pom_xml_resolves_properties_and_scopes() {
+        let mut manifest = empty("java");
+        let pom = r#"<?xml version="1.0"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <parent>
+    <groupId>org.springframework.boot</groupId>
+    <artifactId>spring-boot-starter-parent</artifactId>
+    <version>3.2.0</version>
+  </parent>
+  <artifactId>svc</artifactId>
+  <version>${project.parent.version}</version>
+  <properties><q.version>3.1</q.version></properties>
+  <dependencyManagement><dependencies><dependency>
+    <groupId>io.micronaut</groupId><artifactId>core</artifactId>
+  </dependency></dependencies></dependencyManagement>
+  <build><plugins><plugin>
+    <artifactId>maven-surefire-plugin</artifactId>
+    <dependencies><dependency>
+      <groupId>org.junit</groupId><artifactId>surefire</artifactId>
+    </dependency></dependencies>
+  </plugin></plugins></build>
+  <profiles><profile><dependencies><dependency>
+    <groupId>com.h2database</groupId><artifactId>h2</artifactId>
+  </dependency></dependencies></profile></profiles>
+  <dependencies>
+    <dependency>
+      <groupId>io.quarkus</groupId><artifactId>quarkus-core</artifactId>
+      <version>${q.version}</version><scope>test</scope>
+    </dependency>
+    <dependency>
+      <groupId>javax.servlet</groupId><artifactId>api</artifactId><scope>provided</scope>
+    </dependency>
+  </dependencies>
+</project>"#;
+        pom_xml(pom, &mut manifest).unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("svc"));
+        assert_eq!(manifest.version.as_deref(), Some("3.2.0"));
+        assert_eq!(
+            deps(&manifest),
+            vec![
+                dep(
+                    "org.springframework.boot:spring-boot-starter-parent",
+                    Some("3.2.0"),
+                    "runtime"
+                ),
+                dep("io.quarkus:quarkus-core", Some("3.1"), "dev"),
+                dep("javax.servlet:api", None, "build"),
+            ]
+        );
+        assert!(pom_xml("<project>", &mut empty("java")).is_err());
+    }
+}
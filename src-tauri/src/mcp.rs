@@ -0,0 +1,700 @@
+//! MCP servers Claude Code connects to.
+//!
+//! Servers are configured in three places, highest precedence first:
+//! the project's entry in `~/.claude.json` ("local", private to this user),
+//! `.mcp.json` in the project ("project", shared with the team) and the
+//! top-level `mcpServers` of `~/.claude.json` ("user"). Edits go through
+//! `settings`, so the rest of those files is left alone. `probe_mcp_server`
+//! starts a stdio server the way Claude Code would and checks that it
+//! answers the MCP handshake.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{ChildStdin, ChildStdout};
+
+use crate::safe_write::{Expected, WrittenFile};
+use crate::settings::{SettingsFile, SettingsFileInfo};
+use crate::{current_project_dir, home_dir, resolve_full_path, AppState};
+
+const TRANSPORTS: &[&str] = &["stdio", "sse", "http"];
+/// Protocol version offered in `initialize`
+const PROTOCOL_VERSION: &str = "2025-06-18";
+/// How long a server gets to start and answer
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Server stderr kept for the probe report
+const MAX_STDERR_BYTES: usize = 16 * 1024;
+/// Pages of `tools/list` to follow before giving up
+const MAX_TOOL_PAGES: usize = 20;
+
+/// One configured server
+#[derive(Clone, Serialize)]
+pub struct McpServerInfo {
+    pub name: String,
+    /// "local", "project" or "user"
+    pub scope: String,
+    /// Config file the server is defined in
+    pub path: String,
+    /// "stdio", "sse" or "http"
+    pub transport: String,
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    pub env: Map<String, Value>,
+    pub url: Option<String>,
+    pub headers: Map<String, Value>,
+    /// Other keys on the entry, kept as-is
+    pub extra: Map<String, Value>,
+    /// Why Claude Code can't use the entry, if it can't
+    pub error: Option<String>,
+    /// Scope of a same-named server that takes precedence
+    pub shadowed_by: Option<String>,
+}
+
+/// All servers, plus the state of each config file
+#[derive(Clone, Serialize)]
+pub struct McpOverview {
+    pub files: Vec<SettingsFileInfo>,
+    pub servers: Vec<McpServerInfo>,
+}
+
+/// A server as edited in the UI
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct McpServerInput {
+    pub name: String,
+    /// "stdio" when empty
+    pub transport: String,
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    pub env: Map<String, Value>,
+    pub url: Option<String>,
+    pub headers: Map<String, Value>,
+}
+
+/// A tool a server offers
+#[derive(Clone, Serialize)]
+pub struct McpTool {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Outcome of `probe_mcp_server`
+#[derive(Clone, Serialize)]
+pub struct McpProbeResult {
+    pub ok: bool,
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    /// Protocol version the server agreed to
+    pub protocol_version: Option<String>,
+    pub tools: Vec<McpTool>,
+    /// Time from spawning the server to its `initialize` answer
+    pub initialize_ms: Option<u64>,
+    /// Time taken by `tools/list`
+    pub tools_ms: Option<u64>,
+    pub total_ms: u64,
+    pub error: Option<String>,
+    /// Exit code, if the server exited on its own
+    pub exit_code: Option<i32>,
+    /// Tail of the server's stderr
+    pub stderr: String,
+}
+
+/// `~/.claude.json`, or `.claude.json` in `CLAUDE_CONFIG_DIR`
+fn user_state_file() -> Option<PathBuf> {
+    match std::env::var_os("CLAUDE_CONFIG_DIR") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join(".claude.json")),
+        _ => home_dir().map(|home| home.join(".claude.json")),
+    }
+}
+
+/// Config file of one scope. Local and user servers both live in the
+/// user's `~/.claude.json`, so that file is saved as a user file.
+fn config_file(project_dir: &str, scope: &str) -> Result<SettingsFile, String> {
+    let state_file = || user_state_file().ok_or("Cannot find home directory");
+    match scope {
+        "local" | "user" => Ok(SettingsFile::at("user", state_file()?, project_dir)),
+        "project" => Ok(SettingsFile::at(
+            "mcp",
+            Path::new(project_dir).join(".mcp.json"),
+            project_dir,
+        )),
+        other => Err(format!("Unknown MCP scope: {}", other)),
+    }
+}
+
+/// Path to a scope's `mcpServers` object inside its file
+fn servers_key<'a>(scope: &str, project_dir: &'a str) -> Vec<&'a str> {
+    match scope {
+        "local" => vec!["projects", project_dir, "mcpServers"],
+        _ => vec!["mcpServers"],
+    }
+}
+
+fn servers_of<'a>(
+    settings: &'a Map<String, Value>,
+    scope: &str,
+    project_dir: &str,
+) -> Option<&'a Map<String, Value>> {
+    let mut keys = servers_key(scope, project_dir).into_iter();
+    let mut current = settings.get(keys.next()?)?;
+    for key in keys {
+        current = current.get(key)?;
+    }
+    current.as_object()
+}
+
+/// The scope's `mcpServers` object, created if missing
+fn servers_mut<'a>(
+    settings: &'a mut Map<String, Value>,
+    scope: &str,
+    project_dir: &str,
+) -> Result<&'a mut Map<String, Value>, String> {
+    let mut current = settings;
+    for key in servers_key(scope, project_dir) {
+        current = current
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .ok_or_else(|| format!("`{}` in the MCP config is not an object", key))?;
+    }
+    Ok(current)
+}
+
+fn object_of(value: Option<&Value>) -> Map<String, Value> {
+    value
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn server_info(name: &str, entry: &Value, info: &SettingsFileInfo) -> McpServerInfo {
+    let get_str = |key: &str| entry.get(key).and_then(Value::as_str).map(str::to_string);
+    let transport = get_str("type").unwrap_or_else(|| {
+        // Older entries leave out `type`; a URL means a remote server
+        if entry.get("url").is_some() {
+            "sse".to_string()
+        } else {
+            "stdio".to_string()
+        }
+    });
+    let mut extra = entry.as_object().cloned().unwrap_or_default();
+    for key in ["type", "command", "args", "env", "url", "headers"] {
+        extra.remove(key);
+    }
+    let mut server = McpServerInfo {
+        name: name.to_string(),
+        scope: info.scope.clone(),
+        path: info.path.clone(),
+        transport,
+        command: get_str("command"),
+        args: entry
+            .get("args")
+            .and_then(Value::as_array)
+            .map(|args| {
+                args.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        env: object_of(entry.get("env")),
+        url: get_str("url"),
+        headers: object_of(entry.get("headers")),
+        extra,
+        error: None,
+        shadowed_by: None,
+    };
+    server.error = validate(&McpServerInput {
+        name: server.name.clone(),
+        transport: server.transport.clone(),
+        command: server.command.clone(),
+        args: server.args.clone(),
+        env: server.env.clone(),
+        url: server.url.clone(),
+        headers: server.headers.clone(),
+    })
+    .err();
+    server
+}
+
+fn env_name_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap())
+}
+
+fn validate(server: &McpServerInput) -> Result<(), String> {
+    if server.name.is_empty()
+        || !server
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid server name '{}'; use letters, digits, '-' and '_'",
+            server.name
+        ));
+    }
+    let transport = transport_of(server);
+    if !TRANSPORTS.contains(&transport) {
+        return Err(format!(
+            "Unknown transport '{}'; expected stdio, sse or http",
+            transport
+        ));
+    }
+    if transport == "stdio" {
+        if server
+            .command
+            .as_deref()
+            .is_none_or(|c| c.trim().is_empty())
+        {
+            return Err("A stdio server needs a command".to_string());
+        }
+    } else {
+        let url = server.url.as_deref().unwrap_or("");
+        if !url.starts_with("http://") && !url.starts_with("https://") && !url.starts_with("${") {
+            return Err(format!(
+                "An {} server needs an http:// or https:// URL",
+                transport
+            ));
+        }
+    }
+    for (key, value) in &server.env {
+        if !env_name_pattern().is_match(key) {
+            return Err(format!("Invalid environment variable name '{}'", key));
+        }
+        if !value.is_string() {
+            return Err(format!("Environment variable {} must be a string", key));
+        }
+    }
+    for (key, value) in &server.headers {
+        if key.trim().is_empty() || key.contains(':') {
+            return Err(format!("Invalid header name '{}'", key));
+        }
+        if !value.is_string() {
+            return Err(format!("Header {} must be a string", key));
+        }
+    }
+    Ok(())
+}
+
+fn transport_of(server: &McpServerInput) -> &str {
+    if server.transport.is_empty() {
+        "stdio"
+    } else {
+        &server.transport
+    }
+}
+
+/// Entry JSON, keeping unknown keys from `base`
+fn entry_json(server: &McpServerInput, base: Option<Value>) -> Value {
+    let mut entry = match base {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    for key in ["command", "args", "env", "url", "headers"] {
+        entry.shift_remove(key);
+    }
+    let transport = transport_of(server);
+    entry.insert("type".to_string(), json!(transport));
+    if transport == "stdio" {
+        entry.insert(
+            "command".to_string(),
+            json!(server.command.as_deref().unwrap_or("").trim()),
+        );
+        entry.insert("args".to_string(), json!(server.args));
+        entry.insert("env".to_string(), Value::Object(server.env.clone()));
+    } else {
+        entry.insert("url".to_string(), json!(server.url));
+        if !server.headers.is_empty() {
+            entry.insert("headers".to_string(), Value::Object(server.headers.clone()));
+        }
+    }
+    Value::Object(entry)
+}
+
+/// Servers of one scope, with the file's state
+fn scope_servers(project_dir: &str, scope: &str) -> (SettingsFileInfo, Vec<McpServerInfo>) {
+    let Ok(file) = config_file(project_dir, scope) else {
+        return (
+            SettingsFileInfo {
+                scope: scope.to_string(),
+                path: String::new(),
+                exists: false,
+                hash: None,
+                error: Some("Cannot find home directory".to_string()),
+            },
+            Vec::new(),
+        );
+    };
+    let mut loaded = file.load();
+    // Report the MCP scope, not the file's
+    loaded.info.scope = scope.to_string();
+    let servers = servers_of(&loaded.settings, scope, project_dir)
+        .map(|servers| {
+            servers
+                .iter()
+                .map(|(name, entry)| server_info(name, entry, &loaded.info))
+                .collect()
+        })
+        .unwrap_or_default();
+    (loaded.info, servers)
+}
+
+fn find_server(project_dir: &str, scope: &str, name: &str) -> Result<McpServerInfo, String> {
+    scope_servers(project_dir, scope)
+        .1
+        .into_iter()
+        .find(|server| server.name == name)
+        .ok_or_else(|| format!("No MCP server '{}' in {} config", name, scope))
+}
+
+/// List MCP servers from the local, project and user config
+#[tauri::command]
+pub fn list_mcp_servers(state: State<AppState>) -> Result<McpOverview, String> {
+    let dir = current_project_dir(&state)?;
+    let mut files = Vec::new();
+    let mut servers: Vec<McpServerInfo> = Vec::new();
+    for scope in ["local", "project", "user"] {
+        let (info, found) = scope_servers(&dir, scope);
+        // `~/.claude.json` holds two scopes; list the file once
+        if !files.iter().any(|f: &SettingsFileInfo| f.path == info.path) {
+            files.push(info);
+        }
+        for mut server in found {
+            server.shadowed_by = servers
+                .iter()
+                .find(|s| s.name == server.name)
+                .map(|s| s.scope.clone());
+            servers.push(server);
+        }
+    }
+    Ok(McpOverview { files, servers })
+}
+
+/// Add a server to one scope's config
+#[tauri::command]
+pub fn add_mcp_server(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: String,
+    server: McpServerInput,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    validate(&server)?;
+    let dir = current_project_dir(&state)?;
+    let file = config_file(&dir, &scope)?;
+    let mut loaded = file.load_for_edit()?;
+    let servers = servers_mut(&mut loaded.settings, &scope, &dir)?;
+    if servers.contains_key(&server.name) {
+        return Err(format!(
+            "An MCP server named '{}' already exists in {} config",
+            server.name, scope
+        ));
+    }
+    servers.insert(server.name.clone(), entry_json(&server, None));
+    file.save(&app, &loaded, &expected.unwrap_or_default())
+}
+
+/// Replace a server's entry, renaming it if `server.name` differs from
+/// `name`. Env vars and headers are replaced as a whole.
+#[tauri::command]
+pub fn update_mcp_server(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: String,
+    name: String,
+    server: McpServerInput,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    validate(&server)?;
+    let dir = current_project_dir(&state)?;
+    let file = config_file(&dir, &scope)?;
+    let mut loaded = file.load_for_edit()?;
+    let servers = servers_mut(&mut loaded.settings, &scope, &dir)?;
+    if server.name != name && servers.contains_key(&server.name) {
+        return Err(format!(
+            "An MCP server named '{}' already exists in {} config",
+            server.name, scope
+        ));
+    }
+    let Some(index) = servers.keys().position(|key| *key == name) else {
+        return Err(format!("No MCP server '{}' in {} config", name, scope));
+    };
+    let old = servers.shift_remove(&name);
+    servers.insert(server.name.clone(), entry_json(&server, old));
+    // Keep the entry where it was
+    let last = servers.len() - 1;
+    move_entry(servers, last, index);
+    file.save(&app, &loaded, &expected.unwrap_or_default())
+}
+
+/// Move the entry at `from` to `to`, keeping the order of the others
+fn move_entry(map: &mut Map<String, Value>, from: usize, to: usize) {
+    if from == to {
+        return;
+    }
+    let mut entries: Vec<(String, Value)> = std::mem::take(map).into_iter().collect();
+    let entry = entries.remove(from);
+    entries.insert(to, entry);
+    map.extend(entries);
+}
+
+/// Remove a server from one scope's config
+#[tauri::command]
+pub fn remove_mcp_server(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: String,
+    name: String,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    let dir = current_project_dir(&state)?;
+    let file = config_file(&dir, &scope)?;
+    let mut loaded = file.load_for_edit()?;
+    let servers = servers_mut(&mut loaded.settings, &scope, &dir)?;
+    if servers.shift_remove(&name).is_none() {
+        return Err(format!("No MCP server '{}' in {} config", name, scope));
+    }
+    file.save(&app, &loaded, &expected.unwrap_or_default())
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` like Claude Code does in MCP config
+fn expand_env(value: &str, env: &Map<String, Value>) -> String {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN
+        .get_or_init(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}").unwrap());
+    pattern
+        .replace_all(value, |caps: &Captures| {
+            let name = &caps[1];
+            env.get(name)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .or_else(|| std::env::var(name).ok())
+                .or_else(|| caps.get(2).map(|d| d.as_str().to_string()))
+                .unwrap_or_default()
+        })
+        .to_string()
+}
+
+/// Newline-delimited JSON-RPC over a server's stdio
+struct Session {
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl Session {
+    async fn send(&mut self, message: Value) -> Result<(), String> {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("Server closed its input: {}", e))
+    }
+
+    /// Send a request and wait for its answer, skipping notifications
+    async fn request(&mut self, id: u64, method: &str, params: Value) -> Result<Value, String> {
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+        loop {
+            let line = self
+                .stdout
+                .next_line()
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Server exited before answering {}", method))?;
+            let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                // Servers shouldn't log to stdout, but some do
+                continue;
+            };
+            if message.get("id").and_then(Value::as_u64) != Some(id) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                let text = error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string());
+                return Err(format!("{} failed: {}", method, text));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+}
+
+/// Handshake and tool listing, filling in `result` as it goes
+async fn run_probe(
+    session: &mut Session,
+    started: Instant,
+    result: &mut McpProbeResult,
+) -> Result<(), String> {
+    let init = session
+        .request(
+            1,
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "cc-desktop", "version": env!("CARGO_PKG_VERSION") },
+            }),
+        )
+        .await?;
+    result.initialize_ms = Some(started.elapsed().as_millis() as u64);
+    let get_str =
+        |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    result.protocol_version = get_str(&init, "protocolVersion");
+    if let Some(info) = init.get("serverInfo") {
+        result.server_name = get_str(info, "name");
+        result.server_version = get_str(info, "version");
+    }
+    session
+        .send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+        .await?;
+
+    if init.pointer("/capabilities/tools").is_none() {
+        return Ok(());
+    }
+    let tools_started = Instant::now();
+    let mut cursor: Option<String> = None;
+    for page in 0..MAX_TOOL_PAGES {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        let list = session
+            .request(2 + page as u64, "tools/list", params)
+            .await?;
+        for tool in list
+            .get("tools")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(name) = get_str(tool, "name") {
+                result.tools.push(McpTool {
+                    name,
+                    description: get_str(tool, "description"),
+                });
+            }
+        }
+        cursor = get_str(&list, "nextCursor");
+        if cursor.is_none() {
+            break;
+        }
+    }
+    result.tools_ms = Some(tools_started.elapsed().as_millis() as u64);
+    Ok(())
+}
+
+/// Start a stdio server, run the MCP handshake and list its tools
+#[tauri::command]
+pub async fn probe_mcp_server(
+    state: State<'_, AppState>,
+    scope: String,
+    name: String,
+) -> Result<McpProbeResult, String> {
+    let dir = current_project_dir(&state)?;
+    let server = find_server(&dir, &scope, &name)?;
+    if server.transport != "stdio" {
+        return Err(format!(
+            "Only stdio servers can be probed; '{}' uses {}",
+            name, server.transport
+        ));
+    }
+    if let Some(error) = &server.error {
+        return Err(error.clone());
+    }
+    let command = expand_env(server.command.as_deref().unwrap_or(""), &server.env);
+    let args: Vec<String> = server
+        .args
+        .iter()
+        .map(|arg| expand_env(arg, &server.env))
+        .collect();
+
+    let mut process = tokio::process::Command::new(&command);
+    process
+        .args(&args)
+        .current_dir(&dir)
+        .env("PATH", resolve_full_path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    for (key, value) in &server.env {
+        process.env(key, expand_env(value.as_str().unwrap_or(""), &server.env));
+    }
+
+    let started = Instant::now();
+    let mut child = process
+        .spawn()
+        .map_err(|e| format!("Failed to start '{}': {}", command, e))?;
+    let (Some(stdin), Some(stdout), Some(mut stderr)) =
+        (child.stdin.take(), child.stdout.take(), child.stderr.take())
+    else {
+        return Err("Failed to connect to the server's stdio".to_string());
+    };
+    // Drain stderr so a chatty server can't block on a full pipe
+    let stderr_task = tokio::spawn(async move {
+        let mut tail = Vec::new();
+        let mut chunk = [0u8; 4096];
+        while let Ok(read) = stderr.read(&mut chunk).await {
+            if read == 0 {
+                break;
+            }
+            tail.extend_from_slice(&chunk[..read]);
+            if tail.len() > MAX_STDERR_BYTES {
+                tail.drain(..tail.len() - MAX_STDERR_BYTES);
+            }
+        }
+        tail
+    });
+
+    let mut result = McpProbeResult {
+        ok: false,
+        server_name: None,
+        server_version: None,
+        protocol_version: None,
+        tools: Vec::new(),
+        initialize_ms: None,
+        tools_ms: None,
+        total_ms: 0,
+        error: None,
+        exit_code: None,
+        stderr: String::new(),
+    };
+    let mut session = Session {
+        stdin,
+        stdout: BufReader::new(stdout).lines(),
+    };
+    let outcome =
+        tokio::time::timeout(PROBE_TIMEOUT, run_probe(&mut session, started, &mut result)).await;
+    result.total_ms = started.elapsed().as_millis() as u64;
+    match outcome {
+        Ok(Ok(())) => result.ok = true,
+        Ok(Err(e)) => result.error = Some(e),
+        Err(_) => {
+            result.error = Some(format!(
+                "No answer within {} seconds",
+                PROBE_TIMEOUT.as_secs()
+            ))
+        }
+    }
+
+    drop(session);
+    if let Ok(Some(status)) = child.try_wait() {
+        result.exit_code = status.code();
+    } else {
+        let _ = child.kill().await;
+    }
+    if let Ok(Ok(tail)) = tokio::time::timeout(Duration::from_secs(2), stderr_task).await {
+        result.stderr = String::from_utf8_lossy(&tail).to_string();
+    }
+    Ok(result)
+}
@@ -0,0 +1,489 @@
+//! CLAUDE.md memory files.
+//!
+//! Claude Code loads memory at startup in this order: the managed policy
+//! file, the user's `~/.claude/CLAUDE.md`, then `CLAUDE.md`,
+//! `.claude/CLAUDE.md` and `CLAUDE.local.md` in every directory from the
+//! filesystem root down to the project. `CLAUDE.md` files in subdirectories
+//! are only read when Claude works in them. Any of these can pull in other
+//! files with `@path` imports, up to five levels deep.
+//!
+//! Imports can point anywhere on disk, so the editor only opens imported
+//! text files of a sensible size, and ones outside the project and
+//! `~/.claude` only once the user has confirmed.
+
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::files::hash_file;
+use crate::safe_write::{self, Expected, WrittenFile};
+use crate::{current_project_dir, home_dir, scopes, walk, AppState};
+
+/// Import nesting Claude Code follows
+const MAX_IMPORT_DEPTH: usize = 5;
+
+/// Imports larger than this are neither read nor opened
+const MAX_IMPORT_BYTES: u64 = 512 * 1024;
+
+/// An `@path` import and what it resolves to
+#[derive(Clone, Serialize)]
+pub struct MemoryImport {
+    /// As written, without the `@`
+    pub reference: String,
+    pub path: String,
+    /// 1-based line of the import in the importing file
+    pub line: usize,
+    pub exists: bool,
+    pub tokens: usize,
+    /// Why Claude Code won't follow the import (cycle, too deep)
+    pub error: Option<String>,
+    pub imports: Vec<MemoryImport>,
+}
+
+/// One memory file
+#[derive(Clone, Serialize)]
+pub struct MemoryFile {
+    pub path: String,
+    /// "policy", "user", "parent" (a directory above the project),
+    /// "project", "local" or "nested"
+    pub kind: String,
+    /// Position in the startup load order; `None` for nested files, which
+    /// are read on demand
+    pub order: Option<usize>,
+    pub exists: bool,
+    /// Whether the app may write it (project and user files)
+    pub editable: bool,
+    pub hash: Option<String>,
+    /// Estimated tokens of the file itself
+    pub tokens: usize,
+    /// Estimated tokens including everything it imports
+    pub total_tokens: usize,
+    pub imports: Vec<MemoryImport>,
+}
+
+/// The memory hierarchy of the current project
+#[derive(Clone, Serialize)]
+pub struct MemoryOverview {
+    /// Startup files in load order, then nested ones
+    pub files: Vec<MemoryFile>,
+    /// Estimated tokens loaded into every session
+    pub startup_tokens: usize,
+}
+
+/// Contents of a memory file, for the editor
+#[derive(Clone, Serialize)]
+pub struct MemoryContent {
+    pub path: String,
+    pub content: String,
+    pub hash: Option<String>,
+}
+
+/// Last overview, for `AppState`; reads and writes resolve paths against it
+/// instead of walking the project again
+pub struct OverviewCache {
+    root: String,
+    overview: MemoryOverview,
+}
+
+/// Payload of `claude-memory-changed`
+#[derive(Clone, Serialize)]
+pub struct MemoryChangedEvent {
+    pub path: String,
+}
+
+/// Rough token count: about four characters per token for English and code
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Managed policy file set by an administrator
+fn policy_file() -> PathBuf {
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/ClaudeCode/CLAUDE.md")
+    }
+    #[cfg(target_os = "windows")]
+    {
+        PathBuf::from("C:\\ProgramData\\ClaudeCode\\CLAUDE.md")
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        PathBuf::from("/etc/claude-code/CLAUDE.md")
+    }
+}
+
+fn import_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    // `@` at the start of a word, so e-mail addresses don't count
+    PATTERN.get_or_init(|| Regex::new(r"(?:^|\s)@([^\s`]+)").unwrap())
+}
+
+/// `@path` references outside code blocks and code spans, with their lines
+fn find_imports(content: &str) -> Vec<(String, usize)> {
+    let mut imports = Vec::new();
+    let mut fence: Option<&str> = None;
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            continue;
+        }
+        // Drop code spans: every other backtick-separated piece is code
+        let prose: String = line.split('`').step_by(2).collect::<Vec<_>>().join(" ");
+        for caps in import_pattern().captures_iter(&prose) {
+            let reference = caps[1].trim_end_matches(['.', ',', ';', ':', ')', '!', '?']);
+            if !reference.is_empty() {
+                imports.push((reference.to_string(), index + 1));
+            }
+        }
+    }
+    imports
+}
+
+/// Where an import points: `~/` is the home directory, anything else is
+/// relative to the importing file
+fn resolve_import(reference: &str, from: &Path) -> PathBuf {
+    if let Some(rest) = reference.strip_prefix("~/") {
+        if let Some(home) = home_dir() {
+            return home.join(rest);
+        }
+    }
+    let path = Path::new(reference);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    from.parent().unwrap_or(Path::new("")).join(path)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Imports of `content`, recursively. `stack` holds the files being
+/// expanded, to catch cycles.
+fn resolve_imports(content: &str, from: &Path, stack: &mut Vec<PathBuf>) -> Vec<MemoryImport> {
+    find_imports(content)
+        .into_iter()
+        .map(|(reference, line)| {
+            let path = resolve_import(&reference, from);
+            let mut import = MemoryImport {
+                reference,
+                path: path.to_string_lossy().to_string(),
+                line,
+                exists: path.is_file(),
+                tokens: 0,
+                error: None,
+                imports: Vec::new(),
+            };
+            if !import.exists {
+                return import;
+            }
+            let key = canonical(&path);
+            if stack.contains(&key) {
+                import.error = Some("Circular import; Claude Code skips it".to_string());
+            } else if stack.len() > MAX_IMPORT_DEPTH {
+                import.error = Some(format!(
+                    "Imports nest deeper than {} levels; Claude Code stops here",
+                    MAX_IMPORT_DEPTH
+                ));
+            } else if let Some(size) = std::fs::metadata(&path)
+                .map(|m| m.len())
+                .ok()
+                .filter(|size| *size > MAX_IMPORT_BYTES)
+            {
+                // Not worth reading; estimate from the size
+                import.tokens = size.div_ceil(4) as usize;
+            } else {
+                let text = std::fs::read_to_string(&path).unwrap_or_default();
+                import.tokens = estimate_tokens(&text);
+                stack.push(key);
+                import.imports = resolve_imports(&text, &path, stack);
+                stack.pop();
+            }
+            import
+        })
+        .collect()
+}
+
+fn import_tokens(imports: &[MemoryImport]) -> usize {
+    imports
+        .iter()
+        .map(|import| import.tokens + import_tokens(&import.imports))
+        .sum()
+}
+
+fn memory_file(path: PathBuf, kind: &str, order: Option<usize>, editable: bool) -> MemoryFile {
+    let content = std::fs::read_to_string(&path).ok();
+    let mut stack = vec![canonical(&path)];
+    let imports = content
+        .as_deref()
+        .map(|text| resolve_imports(text, &path, &mut stack))
+        .unwrap_or_default();
+    let tokens = content.as_deref().map(estimate_tokens).unwrap_or(0);
+    MemoryFile {
+        exists: content.is_some(),
+        hash: content.as_ref().and_then(|_| hash_file(&path).ok()),
+        total_tokens: tokens + import_tokens(&imports),
+        tokens,
+        imports,
+        editable,
+        order,
+        kind: kind.to_string(),
+        path: path.to_string_lossy().to_string(),
+    }
+}
+
+/// Startup files of the hierarchy, in load order
+fn startup_files(root: &Path) -> Vec<MemoryFile> {
+    // (path, kind, editable, listed even when missing so it can be created)
+    let mut startup: Vec<(PathBuf, &str, bool, bool)> =
+        vec![(policy_file(), "policy", false, false)];
+    if let Some(dir) = scopes::user_claude_dir() {
+        startup.push((dir.join("CLAUDE.md"), "user", true, true));
+    }
+    let mut ancestors: Vec<&Path> = root.ancestors().collect();
+    ancestors.reverse();
+    for dir in ancestors {
+        // Claude Code stops short of the filesystem root
+        if dir.parent().is_none() {
+            continue;
+        }
+        let is_project = dir == root;
+        let (kind, local_kind) = if is_project {
+            ("project", "local")
+        } else {
+            ("parent", "parent")
+        };
+        startup.push((dir.join("CLAUDE.md"), kind, is_project, is_project));
+        startup.push((
+            dir.join(".claude").join("CLAUDE.md"),
+            kind,
+            is_project,
+            false,
+        ));
+        startup.push((
+            dir.join("CLAUDE.local.md"),
+            local_kind,
+            is_project,
+            is_project,
+        ));
+    }
+
+    let mut files = Vec::new();
+    for (path, kind, editable, listed) in startup {
+        if listed || path.is_file() {
+            let order = files.len();
+            files.push(memory_file(path, kind, Some(order), editable));
+        }
+    }
+    files
+}
+
+/// Whether `path` has the name and place of a nested memory file: below the
+/// project, but not one of its startup files
+fn is_nested(root: &Path, path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let dir = path.parent().unwrap_or(root);
+    (name == "CLAUDE.md" || name == "CLAUDE.local.md")
+        && dir.starts_with(root)
+        && dir != root
+        && dir != root.join(".claude")
+}
+
+/// Files of the hierarchy, startup files in load order first
+fn hierarchy(project_dir: &str) -> Vec<MemoryFile> {
+    let root = Path::new(project_dir);
+    let mut files = startup_files(root);
+    let mut nested: Vec<PathBuf> = walk::walker(root)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| entry.into_path())
+        .filter(|path| is_nested(root, path))
+        .collect();
+    nested.sort();
+    files.extend(
+        nested
+            .into_iter()
+            .map(|path| memory_file(path, "nested", None, true)),
+    );
+    files
+}
+
+fn startup_tokens(files: &[MemoryFile]) -> usize {
+    files
+        .iter()
+        .filter(|file| file.order.is_some())
+        .map(|file| file.total_tokens)
+        .sum()
+}
+
+/// Discover the memory files that apply to the current project. Finding
+/// nested files walks the tree, so it runs off the main thread.
+#[tauri::command]
+pub async fn list_memory_files(state: State<'_, AppState>) -> Result<MemoryOverview, String> {
+    let dir = current_project_dir(&state)?;
+    let files = {
+        let dir = dir.clone();
+        tokio::task::spawn_blocking(move || hierarchy(&dir))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    let overview = MemoryOverview {
+        startup_tokens: startup_tokens(&files),
+        files,
+    };
+    *state.memory_overview.lock().unwrap() = Some(OverviewCache {
+        root: dir,
+        overview: overview.clone(),
+    });
+    Ok(overview)
+}
+
+/// `path` as a file of the hierarchy or one of its imports, read afresh
+fn find_file(files: &[MemoryFile], path: &str) -> Option<MemoryFile> {
+    fn imports_path(imports: &[MemoryImport], path: &str) -> bool {
+        imports
+            .iter()
+            .any(|import| import.path == path || imports_path(&import.imports, path))
+    }
+    if let Some(file) = files.iter().find(|file| file.path == path) {
+        let fresh = memory_file(PathBuf::from(path), &file.kind, file.order, file.editable);
+        return Some(fresh);
+    }
+    files
+        .iter()
+        .any(|file| imports_path(&file.imports, path))
+        .then(|| memory_file(PathBuf::from(path), "import", None, false))
+}
+
+/// A file of the hierarchy, or one of its imports. Looked up in the cached
+/// overview; a path missing from it is checked against the hierarchy rules
+/// on its own, without walking the project. Imports of nested files are
+/// only known from the overview.
+fn known_file(state: &AppState, project_dir: &str, path: &str) -> Result<MemoryFile, String> {
+    let cached = state
+        .memory_overview
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|cache| cache.root == project_dir)
+        .and_then(|cache| find_file(&cache.overview.files, path));
+    let root = Path::new(project_dir);
+    let target = Path::new(path);
+    cached
+        .or_else(|| {
+            let nested = is_nested(root, target)
+                && target.is_file()
+                && !walk::IgnoreMatcher::new(root).is_ignored(target, false);
+            nested.then(|| memory_file(target.to_path_buf(), "nested", None, true))
+        })
+        .or_else(|| find_file(&startup_files(root), path))
+        .ok_or_else(|| format!("{} is not a memory file of this project", path))
+}
+
+/// Put a file that has just been written back into the cached overview
+fn update_cached(state: &AppState, project_dir: &str, file: MemoryFile) {
+    let mut cache = state.memory_overview.lock().unwrap();
+    let Some(cache) = cache.as_mut().filter(|cache| cache.root == project_dir) else {
+        return;
+    };
+    let files = &mut cache.overview.files;
+    if let Some(entry) = files.iter_mut().find(|entry| entry.path == file.path) {
+        *entry = file;
+    }
+    cache.overview.startup_tokens = startup_tokens(files);
+}
+
+/// Contents of an imported file, if it's one the editor may open
+fn read_import(project_dir: &str, path: &Path, confirmed: bool) -> Result<String, String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_IMPORT_BYTES {
+        return Err(format!("{} is too large to open", path.display()));
+    }
+    let target = canonical(path);
+    let inside = [Some(PathBuf::from(project_dir)), scopes::user_claude_dir()]
+        .into_iter()
+        .flatten()
+        .any(|dir| target.starts_with(canonical(&dir)));
+    if !inside && !confirmed {
+        return Err(format!(
+            "{} is outside the project and ~/.claude; confirm to open it",
+            path.display()
+        ));
+    }
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    if walk::looks_binary(&bytes) {
+        return Err(format!("{} is not a text file", path.display()));
+    }
+    String::from_utf8(bytes).map_err(|_| format!("{} is not a text file", path.display()))
+}
+
+/// Read a memory file or one of its imports. Imports outside the project
+/// and `~/.claude` need `confirmed`.
+#[tauri::command]
+pub fn read_memory_file(
+    state: State<AppState>,
+    path: String,
+    confirmed: Option<bool>,
+) -> Result<MemoryContent, String> {
+    let dir = current_project_dir(&state)?;
+    let file = known_file(&state, &dir, &path)?;
+    let content = if !file.exists {
+        String::new()
+    } else if file.kind == "import" {
+        read_import(&dir, Path::new(&path), confirmed.unwrap_or(false))?
+    } else {
+        std::fs::read_to_string(&path).map_err(|e| e.to_string())?
+    };
+    Ok(MemoryContent {
+        path,
+        content,
+        hash: file.hash,
+    })
+}
+
+/// Write a project or user memory file if it still matches `expected`
+#[tauri::command]
+pub fn write_memory_file(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    content: String,
+    expected: Expected,
+) -> Result<WrittenFile, String> {
+    let dir = current_project_dir(&state)?;
+    let file = known_file(&state, &dir, &path)?;
+    if !file.editable {
+        return Err(format!("{} can't be edited from here", path));
+    }
+    let absolute = Path::new(&path);
+    let written = match absolute.strip_prefix(&dir) {
+        Ok(relative) => safe_write::write_in_project(
+            &app,
+            &dir,
+            &relative.to_string_lossy(),
+            content.as_bytes(),
+            &expected,
+        )?,
+        Err(_) => safe_write::write_file(
+            absolute,
+            "CLAUDE.md",
+            content.as_bytes(),
+            &expected,
+            &safe_write::backup_root("user")?,
+        )?,
+    };
+    let fresh = memory_file(absolute.to_path_buf(), &file.kind, file.order, true);
+    update_cached(&state, &dir, fresh);
+    let _ = app.emit("claude-memory-changed", MemoryChangedEvent { path });
+    Ok(written)
+}
@@ -0,0 +1,393 @@
+//! Permission rules from the settings files.
+//!
+//! Each settings file can have `permissions.allow`, `.ask` and `.deny`
+//! lists of rules like `Bash(npm test:*)` or `Read(./secrets/**)`. Claude
+//! Code merges the lists from every file, and a rule in `deny` beats the
+//! same rule in `ask`, which beats `allow`, whichever file they come from.
+//! `get_permissions` shows every rule with the file it came from and whether
+//! a stronger rule overrides it; the edit commands validate rules before
+//! touching a single list in a single file.
+
+use globset::Glob;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::{AppHandle, State};
+
+use crate::lint::KNOWN_TOOLS;
+use crate::safe_write::{Expected, WrittenFile};
+use crate::settings::{self, SettingsFileInfo};
+use crate::{current_project_dir, AppState};
+
+/// Rule lists, strongest first
+const LISTS: &[&str] = &["deny", "ask", "allow"];
+
+/// Tools whose specifier is a gitignore-style path pattern
+const PATH_TOOLS: &[&str] = &[
+    "Edit",
+    "Glob",
+    "Grep",
+    "MultiEdit",
+    "NotebookEdit",
+    "NotebookRead",
+    "Read",
+    "Write",
+];
+
+/// One rule as written in one settings file
+#[derive(Clone, Serialize)]
+pub struct PermissionRule {
+    pub rule: String,
+    /// "allow", "ask" or "deny"
+    pub list: String,
+    /// Settings scope: "local", "project" or "user"
+    pub scope: String,
+    pub path: String,
+    pub tool: String,
+    /// The part in parentheses, e.g. `npm test:*`
+    pub specifier: Option<String>,
+    /// Why Claude Code won't understand the rule, if it won't
+    pub error: Option<String>,
+    /// The stronger or higher-precedence entry that makes this one moot,
+    /// e.g. "deny in project settings"
+    pub overridden_by: Option<String>,
+}
+
+/// A single-valued setting and the scope it comes from
+#[derive(Clone, Serialize)]
+pub struct ScopedValue {
+    pub value: String,
+    pub scope: String,
+}
+
+/// Merged permissions of the current project
+#[derive(Clone, Serialize)]
+pub struct PermissionsOverview {
+    pub files: Vec<SettingsFileInfo>,
+    /// Strongest list first, then highest-precedence file first
+    pub rules: Vec<PermissionRule>,
+    /// `permissions.defaultMode` from the highest-precedence file setting it
+    pub default_mode: Option<ScopedValue>,
+    /// `permissions.additionalDirectories` from every file
+    pub additional_directories: Vec<ScopedValue>,
+}
+
+/// A rule split into tool and specifier
+#[derive(Clone, Serialize)]
+pub struct RuleSyntax {
+    pub tool: String,
+    pub specifier: Option<String>,
+}
+
+fn scope_label(scope: &str) -> &'static str {
+    match scope {
+        "local" => "local project settings",
+        "project" => "project settings",
+        _ => "user settings",
+    }
+}
+
+fn validate_list(list: &str) -> Result<(), String> {
+    if LISTS.contains(&list) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown permission list '{}'; expected allow, ask or deny",
+            list
+        ))
+    }
+}
+
+/// Check a rule the way Claude Code reads it: `Tool` or `Tool(specifier)`
+pub(crate) fn parse_rule(rule: &str) -> Result<RuleSyntax, String> {
+    let rule = rule.trim();
+    if rule.is_empty() {
+        return Err("Rule is empty".to_string());
+    }
+    let (tool, specifier) = match rule.find('(') {
+        Some(open) => {
+            let inner = rule[open + 1..]
+                .strip_suffix(')')
+                .ok_or_else(|| format!("Rule '{}' is missing its closing ')'", rule))?;
+            if inner.trim().is_empty() {
+                return Err(format!(
+                    "Rule '{}' has empty parentheses; use '{}' to match every use",
+                    rule,
+                    &rule[..open]
+                ));
+            }
+            (rule[..open].trim(), Some(inner.to_string()))
+        }
+        None if rule.contains(')') => return Err(format!("Rule '{}' has a stray ')'", rule)),
+        None => (rule, None),
+    };
+
+    if tool.starts_with("mcp__") {
+        if specifier.is_some() {
+            return Err(format!(
+                "MCP rules take no parentheses; use '{}' or '{}__<tool>'",
+                tool, tool
+            ));
+        }
+        if tool.trim_start_matches("mcp__").is_empty() || tool.ends_with("__") {
+            return Err(format!("Rule '{}' is missing an MCP server name", rule));
+        }
+    } else if !KNOWN_TOOLS.contains(&tool) {
+        let suggestion = KNOWN_TOOLS
+            .iter()
+            .find(|known| known.eq_ignore_ascii_case(tool))
+            .map(|known| format!(" (did you mean '{}'?)", known))
+            .unwrap_or_default();
+        return Err(format!("Unknown tool '{}'{}", tool, suggestion));
+    }
+
+    if let Some(spec) = &specifier {
+        match tool {
+            // `:*` is a prefix match and only means that at the very end
+            "Bash" if spec.trim_end_matches(":*").contains(":*") => {
+                return Err(format!(
+                    "In '{}', ':*' only works at the end of the command",
+                    rule
+                ));
+            }
+            "WebFetch" => {
+                let domain = spec.strip_prefix("domain:").ok_or_else(|| {
+                    "WebFetch rules look like 'WebFetch(domain:example.com)'".to_string()
+                })?;
+                if domain.trim().is_empty() || domain.contains('/') {
+                    return Err(format!("'{}' is not a domain", domain));
+                }
+            }
+            tool if PATH_TOOLS.contains(&tool) => {
+                Glob::new(spec).map_err(|e| format!("Invalid path pattern '{}': {}", spec, e))?;
+            }
+            _ => {}
+        }
+    }
+    Ok(RuleSyntax {
+        tool: tool.to_string(),
+        specifier,
+    })
+}
+
+/// Rules of one list in one file's settings
+fn list_rules(settings: &Map<String, Value>, list: &str) -> Vec<String> {
+    settings
+        .get("permissions")
+        .and_then(|p| p.get(list))
+        .and_then(Value::as_array)
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Mark rules made moot by the same rule in a stronger list, or repeated
+/// from a higher-precedence file. `rules` must be strongest first.
+fn mark_overridden(rules: &mut [PermissionRule]) {
+    for i in 0..rules.len() {
+        let winner = rules[..i]
+            .iter()
+            .find(|earlier| earlier.rule.trim() == rules[i].rule.trim());
+        if let Some(winner) = winner {
+            rules[i].overridden_by =
+                Some(format!("{} in {}", winner.list, scope_label(&winner.scope)));
+        }
+    }
+}
+
+/// Read and merge the permissions of the local, project and user settings
+#[tauri::command]
+pub fn get_permissions(state: State<AppState>) -> Result<PermissionsOverview, String> {
+    let dir = current_project_dir(&state)?;
+    let loaded: Vec<_> = settings::settings_files(&dir)
+        .iter()
+        .map(|file| file.load())
+        .collect();
+
+    let mut rules = Vec::new();
+    for list in LISTS {
+        for file in &loaded {
+            for rule in list_rules(&file.settings, list) {
+                let (tool, specifier, error) = match parse_rule(&rule) {
+                    Ok(syntax) => (syntax.tool, syntax.specifier, None),
+                    Err(e) => (String::new(), None, Some(e)),
+                };
+                rules.push(PermissionRule {
+                    rule,
+                    list: list.to_string(),
+                    scope: file.info.scope.clone(),
+                    path: file.info.path.clone(),
+                    tool,
+                    specifier,
+                    error,
+                    overridden_by: None,
+                });
+            }
+        }
+    }
+    mark_overridden(&mut rules);
+
+    let permissions = |file: &settings::Loaded| file.settings.get("permissions").cloned();
+    let default_mode = loaded.iter().find_map(|file| {
+        let mode = permissions(file)?.get("defaultMode")?.as_str()?.to_string();
+        Some(ScopedValue {
+            value: mode,
+            scope: file.info.scope.clone(),
+        })
+    });
+    let additional_directories = loaded
+        .iter()
+        .flat_map(|file| {
+            list_rules(&file.settings, "additionalDirectories")
+                .into_iter()
+                .map(|value| ScopedValue {
+                    value,
+                    scope: file.info.scope.clone(),
+                })
+        })
+        .collect();
+
+    Ok(PermissionsOverview {
+        files: loaded.into_iter().map(|file| file.info).collect(),
+        rules,
+        default_mode,
+        additional_directories,
+    })
+}
+
+/// Check a rule without saving it
+#[tauri::command]
+pub fn validate_permission_rule(rule: String) -> Result<RuleSyntax, String> {
+    parse_rule(&rule)
+}
+
+/// `permissions.<list>`, created if missing
+fn rule_list<'a>(
+    settings: &'a mut Map<String, Value>,
+    list: &str,
+) -> Result<&'a mut Vec<Value>, String> {
+    settings
+        .entry("permissions")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or("`permissions` in settings is not an object")?
+        .entry(list)
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or_else(|| format!("`permissions.{}` in settings is not a list", list))
+}
+
+/// Drop `rule` from a list, and the list and `permissions` if left empty
+fn drop_rule(settings: &mut Map<String, Value>, list: &str, rule: &str) -> Result<bool, String> {
+    let rules = rule_list(settings, list)?;
+    let before = rules.len();
+    rules.retain(|r| r.as_str().map(str::trim) != Some(rule));
+    let removed = rules.len() != before;
+    if rules.is_empty() {
+        if let Some(permissions) = settings
+            .get_mut("permissions")
+            .and_then(Value::as_object_mut)
+        {
+            permissions.shift_remove(list);
+            if permissions.is_empty() {
+                settings.shift_remove("permissions");
+            }
+        }
+    }
+    Ok(removed)
+}
+
+/// Add a rule to a list in one settings file. The same rule is taken out of
+/// that file's other lists, so this also moves a rule between lists.
+#[tauri::command]
+pub fn add_permission_rule(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: String,
+    list: String,
+    rule: String,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    validate_list(&list)?;
+    parse_rule(&rule)?;
+    let rule = rule.trim();
+    let dir = current_project_dir(&state)?;
+    let file = settings::settings_file(&dir, &scope)?;
+    let mut loaded = file.load_for_edit()?;
+
+    for other in LISTS.iter().filter(|other| **other != list) {
+        drop_rule(&mut loaded.settings, other, rule)?;
+    }
+    let rules = rule_list(&mut loaded.settings, &list)?;
+    if !rules
+        .iter()
+        .any(|r| r.as_str().map(str::trim) == Some(rule))
+    {
+        rules.push(Value::String(rule.to_string()));
+    }
+    file.save(&app, &loaded, &expected.unwrap_or_default())
+}
+
+/// Remove a rule from a list in one settings file
+#[tauri::command]
+pub fn remove_permission_rule(
+    app: AppHandle,
+    state: State<AppState>,
+    scope: String,
+    list: String,
+    rule: String,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    validate_list(&list)?;
+    let dir = current_project_dir(&state)?;
+    let file = settings::settings_file(&dir, &scope)?;
+    let mut loaded = file.load_for_edit()?;
+    if !drop_rule(&mut loaded.settings, &list, rule.trim())? {
+        return Err(format!(
+            "'{}' is not in the {} list of {}",
+            rule,
+            list,
+            scope_label(&scope)
+        ));
+    }
+    file.save(&app, &loaded, &expected.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rule_splits_tool_and_specifier() {
+        let rule = parse_rule(" Bash(npm run test:*) ").unwrap();
+        assert_eq!(rule.tool, "Bash");
+        assert_eq!(rule.specifier.as_deref(), Some("npm run test:*"));
+        assert!(parse_rule("Read").unwrap().specifier.is_none());
+        assert!(parse_rule("WebFetch(domain:example.com)").is_ok());
+        assert!(parse_rule("mcp__github__create_issue").is_ok());
+    }
+
+    #[test]
+    fn parse_rule_rejects_malformed_rules() {
+        assert!(parse_rule("").is_err());
+        assert!(parse_rule("Bash(ls").err().unwrap().contains("closing"));
+        assert!(parse_rule("Bash()")
+            .err()
+            .unwrap()
+            .contains("empty parentheses"));
+        assert!(parse_rule("Bash)").err().unwrap().contains("stray"));
+        assert!(parse_rule("bash")
+            .err()
+            .unwrap()
+            .contains("did you mean 'Bash'"));
+        assert!(parse_rule("Bash(git:* push)").is_err());
+        assert!(parse_rule("WebFetch(example.com)").is_err());
+        assert!(parse_rule("mcp__github(x)").is_err());
+        assert!(parse_rule("mcp__").is_err());
+        assert!(parse_rule("Edit(src/[)").is_err());
+    }
+}
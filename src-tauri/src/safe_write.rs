@@ -0,0 +1,254 @@
+//! Conflict-checked, atomic file writes.
+//!
+//! Claude may edit the same files the app does (agents, skills, CLAUDE.md,
+//! settings), so every write states what it expects to replace. If the file
+//! on disk no longer matches, the write is refused instead of clobbering the
+//! other edit. Content goes to a temp file in the same directory and is
+//! renamed over the target, and the previous version is kept as a backup.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::files::{hash_file, mtime_millis, resolve_in_project};
+use crate::{app_data_dir, current_project_dir, now_millis, project_key, walk, AppState};
+
+/// Backups kept per file; older ones are pruned
+const MAX_BACKUPS: usize = 10;
+
+/// What the caller last saw of the file. With neither field set the file
+/// must not exist yet.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Expected {
+    /// SHA-256 from `read_project_file`
+    pub hash: Option<String>,
+    /// Unix milliseconds from `read_project_file`
+    pub mtime: Option<u64>,
+}
+
+/// Result of a write, also the payload of `project-file-written`
+#[derive(Clone, Serialize)]
+pub struct WrittenFile {
+    pub path: String,
+    pub hash: String,
+    pub mtime: u64,
+    /// The file didn't exist before
+    pub created: bool,
+    /// Where the previous version was saved
+    pub backup: Option<String>,
+}
+
+/// Backup location for files belonging to `scope`, e.g. a project key
+pub(crate) fn backup_root(scope: &str) -> Result<PathBuf, String> {
+    Ok(app_data_dir()?.join("backups").join(scope))
+}
+
+/// Fail unless the file at `path` is still what the caller expects
+pub(crate) fn check_expected(
+    path: &Path,
+    display: &str,
+    expected: &Expected,
+) -> Result<(), String> {
+    let meta = match std::fs::metadata(path) {
+        Ok(meta) => Some(meta),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.to_string()),
+    };
+    let Some(meta) = meta else {
+        if expected.hash.is_some() || expected.mtime.is_some() {
+            return Err(format!(
+                "Conflict: {} was deleted since it was read",
+                display
+            ));
+        }
+        return Ok(());
+    };
+    if !meta.is_file() {
+        return Err(format!("Not a file: {}", display));
+    }
+    if expected.hash.is_none() && expected.mtime.is_none() {
+        return Err(format!("Conflict: {} already exists", display));
+    }
+    if let Some(hash) = &expected.hash {
+        if hash_file(path)? != *hash {
+            return Err(format!("Conflict: {} changed since it was read", display));
+        }
+    } else if expected.mtime != Some(mtime_millis(&meta)) {
+        return Err(format!("Conflict: {} changed since it was read", display));
+    }
+    Ok(())
+}
+
+/// Copy the current version of `path` to `<backups>/<relative>.<millis>.bak`
+/// and prune old backups of the same file
+fn backup(path: &Path, backups: &Path, relative: &str) -> Result<PathBuf, String> {
+    let target = backups.join(format!("{}.{}.bak", relative, now_millis()));
+    let dir = target.parent().ok_or("Invalid backup path")?;
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    std::fs::copy(path, &target).map_err(|e| format!("Failed to back up {}: {}", relative, e))?;
+
+    let name = Path::new(relative)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let mut existing: Vec<(u64, PathBuf)> = std::fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let stamp = file_name
+                .strip_prefix(&name)?
+                .strip_prefix('.')?
+                .strip_suffix(".bak")?
+                .parse()
+                .ok()?;
+            Some((stamp, entry.path()))
+        })
+        .collect();
+    existing.sort_by_key(|(stamp, _)| std::cmp::Reverse(*stamp));
+    for (_, old) in existing.into_iter().skip(MAX_BACKUPS) {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(target)
+}
+
+/// Write `content` to `path` if it still matches `expected`.
+/// `relative` names the file in messages and under `backups`.
+pub(crate) fn write_file(
+    path: &Path,
+    relative: &str,
+    content: &[u8],
+    expected: &Expected,
+    backups: &Path,
+) -> Result<WrittenFile, String> {
+    check_expected(path, relative, expected)?;
+    let existed = path.exists();
+    let dir = path.parent().ok_or("Invalid path")?;
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = dir.join(format!(
+        ".{}.cc-tmp-{}-{}",
+        file_name,
+        std::process::id(),
+        now_millis()
+    ));
+    let written = (|| -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        if existed {
+            let permissions = std::fs::metadata(path)?.permissions();
+            std::fs::set_permissions(&temp, permissions)?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp);
+        return Err(format!("Failed to write {}: {}", relative, e));
+    }
+
+    // Check again right before replacing, in case of a write in between
+    let backup_path = match check_expected(path, relative, expected) {
+        Ok(()) if existed => backup(path, backups, relative).map(Some),
+        Ok(()) => Ok(None),
+        Err(e) => Err(e),
+    };
+    let backup_path = match backup_path {
+        Ok(backup_path) => backup_path,
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e);
+        }
+    };
+    if let Err(e) = std::fs::rename(&temp, path) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(format!("Failed to replace {}: {}", relative, e));
+    }
+
+    let meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    Ok(WrittenFile {
+        path: relative.to_string(),
+        hash: hash_file(path)?,
+        mtime: mtime_millis(&meta),
+        created: !existed,
+        backup: backup_path.map(|p| p.to_string_lossy().to_string()),
+    })
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Delete a file or directory by moving it to
+/// `<backups>/<relative>.<millis>.bak`. Callers check expectations first.
+pub(crate) fn remove_to_backup(
+    path: &Path,
+    relative: &str,
+    backups: &Path,
+) -> Result<PathBuf, String> {
+    let target = backups.join(format!("{}.{}.bak", relative, now_millis()));
+    let dir = target.parent().ok_or("Invalid backup path")?;
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    if std::fs::rename(path, &target).is_err() {
+        // Backups may live on another filesystem
+        copy_recursive(path, &target)
+            .map_err(|e| format!("Failed to back up {}: {}", relative, e))?;
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+        removed.map_err(|e| format!("Failed to remove {}: {}", relative, e))?;
+    }
+    Ok(target)
+}
+
+/// Write a project file, then tell open views via `project-file-written`
+pub(crate) fn write_in_project(
+    app: &AppHandle,
+    project_dir: &str,
+    relative: &str,
+    content: &[u8],
+    expected: &Expected,
+) -> Result<WrittenFile, String> {
+    let absolute = resolve_in_project(project_dir, relative)?;
+    let root = std::fs::canonicalize(project_dir).map_err(|e| e.to_string())?;
+    let relative = walk::relative_path(&root, &absolute);
+    let backups = backup_root(&project_key(project_dir))?;
+    let written = write_file(&absolute, &relative, content, expected, &backups)?;
+    let _ = app.emit("project-file-written", written.clone());
+    Ok(written)
+}
+
+/// Write a text file in the project. Pass the `hash` (or `mtime`) from
+/// `read_project_file`; omit both to create a new file.
+#[tauri::command]
+pub fn write_project_file(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    content: String,
+    expected: Option<Expected>,
+) -> Result<WrittenFile, String> {
+    let dir = current_project_dir(&state)?;
+    write_in_project(
+        &app,
+        &dir,
+        &path,
+        content.as_bytes(),
+        &expected.unwrap_or_default(),
+    )
+}
@@ -0,0 +1,191 @@
+//! Where Claude Code looks for agents, skills and commands.
+//!
+//! Three scopes contribute, in order of precedence: the project's
+//! `.claude/`, the user's `~/.claude/` and installed plugins. An item that
+//! exists in several scopes under the same name is taken from the first one;
+//! the others are still listed but marked as shadowed. Plugin items are
+//! namespaced with the plugin name (`plugin:item`), so they only shadow each
+//! other.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::files::hash_file;
+use crate::home_dir;
+
+/// A directory laid out like `.claude/` (with `agents/`, `skills/`, `commands/`)
+pub(crate) struct ConfigRoot {
+    /// "project", "user" or "plugin"
+    pub scope: &'static str,
+    pub dir: PathBuf,
+    /// Plugin name, for plugin roots
+    pub plugin: Option<String>,
+}
+
+impl ConfigRoot {
+    /// The project's own `.claude/`
+    pub(crate) fn project(project_dir: &str) -> Self {
+        ConfigRoot {
+            scope: "project",
+            dir: Path::new(project_dir).join(".claude"),
+            plugin: None,
+        }
+    }
+
+    /// Item name as Claude Code addresses it: plugin items are prefixed
+    pub(crate) fn qualify(&self, name: &str) -> String {
+        match &self.plugin {
+            Some(plugin) => format!("{}:{}", plugin, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Where an item came from, before shadowing is resolved
+    pub(crate) fn origin(&self, path: &Path) -> Origin {
+        Origin {
+            scope: self.scope.to_string(),
+            source_path: path.to_string_lossy().to_string(),
+            plugin: self.plugin.clone(),
+            hash: hash_file(path).ok(),
+            shadowed_by: None,
+        }
+    }
+}
+
+/// Scope information attached to every discovered item
+#[derive(Clone, Serialize)]
+pub struct Origin {
+    /// "project", "user" or "plugin"
+    pub scope: String,
+    /// Absolute path of the item's file
+    pub source_path: String,
+    pub plugin: Option<String>,
+    /// SHA-256 of the file, to pass back as the expected hash when editing
+    pub hash: Option<String>,
+    /// `source_path` of the item that takes precedence over this one
+    pub shadowed_by: Option<String>,
+}
+
+/// The user's Claude config directory, honouring `CLAUDE_CONFIG_DIR`
+pub(crate) fn user_claude_dir() -> Option<PathBuf> {
+    match std::env::var_os("CLAUDE_CONFIG_DIR") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => home_dir().map(|home| home.join(".claude")),
+    }
+}
+
+/// One install record in `installed_plugins.json`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginInstall {
+    install_path: Option<PathBuf>,
+}
+
+/// Older files map a plugin to one install, newer ones to a list
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PluginInstalls {
+    One(PluginInstall),
+    Many(Vec<PluginInstall>),
+}
+
+#[derive(Deserialize)]
+struct InstalledPlugins {
+    #[serde(default)]
+    plugins: HashMap<String, PluginInstalls>,
+}
+
+/// Plugins switched off with `"enabledPlugins": {"name@marketplace": false}`
+fn disabled_plugins(user_dir: &Path) -> Vec<String> {
+    let Ok(content) = std::fs::read_to_string(user_dir.join("settings.json")) else {
+        return Vec::new();
+    };
+    let Ok(settings) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return Vec::new();
+    };
+    settings
+        .get("enabledPlugins")
+        .and_then(|v| v.as_object())
+        .map(|enabled| {
+            enabled
+                .iter()
+                .filter(|(_, on)| on.as_bool() == Some(false))
+                .map(|(id, _)| id.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Installed, enabled plugins as config roots, sorted by name
+fn plugin_roots(user_dir: &Path) -> Vec<ConfigRoot> {
+    let manifest = user_dir.join("plugins").join("installed_plugins.json");
+    let Ok(content) = std::fs::read_to_string(&manifest) else {
+        return Vec::new();
+    };
+    let Ok(installed) = serde_json::from_str::<InstalledPlugins>(&content) else {
+        return Vec::new();
+    };
+    let disabled = disabled_plugins(user_dir);
+
+    let mut roots: Vec<ConfigRoot> = installed
+        .plugins
+        .into_iter()
+        .filter(|(id, _)| !disabled.contains(id))
+        .filter_map(|(id, installs)| {
+            let install = match installs {
+                PluginInstalls::One(install) => install,
+                PluginInstalls::Many(list) => {
+                    list.into_iter().find(|i| i.install_path.is_some())?
+                }
+            };
+            let dir = install.install_path.filter(|p| p.is_dir())?;
+            // Plugin ids are `name@marketplace`; items use just the name
+            let name = id.split('@').next().unwrap_or(&id).to_string();
+            Some(ConfigRoot {
+                scope: "plugin",
+                dir,
+                plugin: Some(name),
+            })
+        })
+        .collect();
+    roots.sort_by(|a, b| a.plugin.cmp(&b.plugin));
+    roots
+}
+
+/// All config roots for a project, highest precedence first
+pub(crate) fn config_roots(project_dir: &str) -> Vec<ConfigRoot> {
+    let mut roots = vec![ConfigRoot::project(project_dir)];
+    if let Some(user_dir) = user_claude_dir() {
+        // The project might be the home directory itself
+        if user_dir != roots[0].dir {
+            roots.push(ConfigRoot {
+                scope: "user",
+                dir: user_dir.clone(),
+                plugin: None,
+            });
+        }
+        roots.extend(plugin_roots(&user_dir));
+    }
+    roots
+}
+
+/// Mark every item whose `key` was already seen as shadowed. `items` must be
+/// in precedence order, as produced by walking `config_roots`.
+pub(crate) fn mark_shadowed<T>(
+    items: &mut [T],
+    key: impl Fn(&T) -> String,
+    origin: impl Fn(&mut T) -> &mut Origin,
+) {
+    let mut winners: HashMap<String, String> = HashMap::new();
+    for item in items.iter_mut() {
+        let key = key(item);
+        let origin = origin(item);
+        match winners.get(&key) {
+            Some(winner) => origin.shadowed_by = Some(winner.clone()),
+            None => {
+                winners.insert(key, origin.source_path.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Project-wide content search.
+//!
+//! Walks the project with the standard ignore rules, skips binary and
+//! oversized files, and streams matches to the frontend as they are found.
+//! Only one search runs at a time: starting a new one cancels the previous,
+//! mirroring how `run_claude_prompt`/`stop_claude` share `child_pid`.
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{current_project_dir, walk, AppState};
+
+const DEFAULT_CONTEXT_LINES: usize = 2;
+const DEFAULT_MAX_RESULTS: usize = 2000;
+/// Files larger than this are skipped (minified bundles, dumps, …)
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+/// Lines longer than this are cut in results so one minified line can't flood the UI
+const MAX_LINE_CHARS: usize = 500;
+
+/// Source of search ids; unique even for searches started in the same millisecond
+static NEXT_SEARCH_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Search options from the frontend; everything is optional
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Treat the pattern as a regular expression instead of literal text
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Only match whole words
+    pub whole_word: bool,
+    /// Globs a file must match, e.g. `src/**` or `*.ts`
+    pub include: Vec<String>,
+    /// Globs that exclude files and directories
+    pub exclude: Vec<String>,
+    pub context_lines: Option<usize>,
+    pub max_results: Option<usize>,
+}
+
+/// One matching line
+#[derive(Clone, Serialize)]
+pub struct SearchMatch {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based character column of the first match in the file's line, which
+    /// may lie past the end of a clipped `text`
+    pub column: usize,
+    /// The line, cut at `MAX_LINE_CHARS` characters
+    pub text: String,
+    /// Character ranges `[start, end)` of every match within `text`; matches
+    /// past the cut are left out
+    pub ranges: Vec<(usize, usize)>,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Payload of `search-results`: all matches in one file
+#[derive(Clone, Serialize)]
+pub struct SearchFileEvent {
+    pub search_id: String,
+    pub path: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// Payload of `search-done`, also returned by `search_content`
+#[derive(Clone, Serialize)]
+pub struct SearchSummary {
+    pub search_id: String,
+    pub files_searched: usize,
+    pub files_matched: usize,
+    pub matches: usize,
+    /// Stopped at `max_results`
+    pub truncated: bool,
+    pub cancelled: bool,
+}
+
+/// Payload of `search-started`
+#[derive(Clone, Serialize)]
+pub struct SearchStartedEvent {
+    pub search_id: String,
+    pub pattern: String,
+}
+
+/// Compiled form of a pattern plus options
+struct Query {
+    re: Regex,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    context: usize,
+    max_results: usize,
+}
+
+/// The search in progress and its cancel flag
+pub struct ActiveSearch {
+    id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+fn build_globs(patterns: &[String]) -> Result<Option<GlobSet>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim().trim_start_matches("./");
+        let glob = Glob::new(pattern).map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+        // `dir` or `dir/` should also cover everything underneath
+        if !pattern.contains('*') {
+            let nested = format!("{}/**", pattern.trim_end_matches('/'));
+            builder.add(Glob::new(&nested).map_err(|e| e.to_string())?);
+        }
+    }
+    builder.build().map(Some).map_err(|e| e.to_string())
+}
+
+fn build_regex(pattern: &str, options: &SearchOptions) -> Result<Regex, String> {
+    let mut source = if options.regex {
+        pattern.to_string()
+    } else {
+        regex::escape(pattern)
+    };
+    if options.whole_word {
+        source = format!(r"\b(?:{})\b", source);
+    }
+    RegexBuilder::new(&source)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+fn clip(line: &str) -> String {
+    if line.chars().count() > MAX_LINE_CHARS {
+        let mut clipped: String = line.chars().take(MAX_LINE_CHARS).collect();
+        clipped.push('…');
+        clipped
+    } else {
+        line.to_string()
+    }
+}
+
+/// Find matching lines in one file's text
+fn search_text(text: &str, re: &Regex, context: usize, budget: usize) -> Vec<SearchMatch> {
+    let lines: Vec<&str> = text
+        .split('\n')
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .collect();
+    let mut matches = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if matches.len() >= budget {
+            break;
+        }
+        let mut found = re
+            .find_iter(line)
+            .filter(|m| !m.is_empty())
+            .map(|m| {
+                let start = line[..m.start()].chars().count();
+                (start, start + m.as_str().chars().count())
+            })
+            .peekable();
+        let Some(&(first, _)) = found.peek() else {
+            continue;
+        };
+        // `text` is clipped, so keep only the part of each match inside it
+        let ranges = found
+            .take_while(|(start, _)| *start < MAX_LINE_CHARS)
+            .map(|(start, end)| (start, end.min(MAX_LINE_CHARS)))
+            .collect();
+        let before_start = index.saturating_sub(context);
+        let after_end = (index + 1 + context).min(lines.len());
+        matches.push(SearchMatch {
+            line: index + 1,
+            column: first + 1,
+            text: clip(line),
+            ranges,
+            before: lines[before_start..index].iter().map(|l| clip(l)).collect(),
+            after: lines[index + 1..after_end]
+                .iter()
+                .map(|l| clip(l))
+                .collect(),
+        });
+    }
+    matches
+}
+
+/// Walk the project and stream matches. Runs on a blocking thread.
+fn run_search(
+    app: &AppHandle,
+    root: &Path,
+    search_id: &str,
+    query: Query,
+    cancelled: &AtomicBool,
+) -> SearchSummary {
+    let Query {
+        re,
+        include,
+        exclude,
+        context,
+        max_results,
+    } = query;
+    let mut summary = SearchSummary {
+        search_id: search_id.to_string(),
+        files_searched: 0,
+        files_matched: 0,
+        matches: 0,
+        truncated: false,
+        cancelled: false,
+    };
+
+    let mut builder = walk::walker(root);
+    let filter_root = root.to_path_buf();
+    builder.filter_entry(move |entry| {
+        if entry.file_name() == ".git" {
+            return false;
+        }
+        let relative = walk::relative_path(&filter_root, entry.path());
+        !exclude.as_ref().is_some_and(|set| set.is_match(&relative))
+    });
+
+    for entry in builder.build().flatten() {
+        if cancelled.load(Ordering::Relaxed) {
+            summary.cancelled = true;
+            break;
+        }
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let relative = walk::relative_path(root, entry.path());
+        if include.as_ref().is_some_and(|set| !set.is_match(&relative)) {
+            continue;
+        }
+        if entry.metadata().map_or(true, |m| m.len() > MAX_FILE_BYTES) {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(entry.path()) else {
+            continue;
+        };
+        if walk::looks_binary(&bytes) {
+            continue;
+        }
+        summary.files_searched += 1;
+
+        let text = String::from_utf8_lossy(&bytes);
+        let budget = max_results - summary.matches;
+        let matches = search_text(&text, &re, context, budget);
+        if matches.is_empty() {
+            continue;
+        }
+        summary.files_matched += 1;
+        summary.matches += matches.len();
+        let _ = app.emit(
+            "search-results",
+            SearchFileEvent {
+                search_id: search_id.to_string(),
+                path: relative,
+                matches,
+            },
+        );
+        if summary.matches >= max_results {
+            summary.truncated = true;
+            break;
+        }
+    }
+    summary
+}
+
+/// Search file contents across the project. Emits `search-started`, then
+/// `search-results` per matching file, then `search-done`.
+#[tauri::command]
+pub async fn search_content(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    pattern: String,
+    options: Option<SearchOptions>,
+) -> Result<SearchSummary, String> {
+    let dir = current_project_dir(&state)?;
+    let options = options.unwrap_or_default();
+    if pattern.is_empty() {
+        return Err("Search pattern is empty".to_string());
+    }
+    let query = Query {
+        re: build_regex(&pattern, &options)?,
+        include: build_globs(&options.include)?,
+        exclude: build_globs(&options.exclude)?,
+        context: options.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES),
+        max_results: options.max_results.unwrap_or(DEFAULT_MAX_RESULTS).max(1),
+    };
+
+    // Replace (and cancel) whatever search was running
+    let search_id = format!("search-{}", NEXT_SEARCH_ID.fetch_add(1, Ordering::Relaxed));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let previous = state.active_search.lock().unwrap().replace(ActiveSearch {
+        id: search_id.clone(),
+        cancelled: cancelled.clone(),
+    });
+    if let Some(previous) = previous {
+        previous.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    app.emit(
+        "search-started",
+        SearchStartedEvent {
+            search_id: search_id.clone(),
+            pattern,
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    let worker_app = app.clone();
+    let worker_id = search_id.clone();
+    let worker_cancelled = cancelled.clone();
+    let summary = tokio::task::spawn_blocking(move || {
+        run_search(
+            &worker_app,
+            Path::new(&dir),
+            &worker_id,
+            query,
+            &worker_cancelled,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    {
+        let mut active = state.active_search.lock().unwrap();
+        if active.as_ref().is_some_and(|a| a.id == search_id) {
+            *active = None;
+        }
+    }
+    app.emit("search-done", summary.clone())
+        .map_err(|e| e.to_string())?;
+    Ok(summary)
+}
+
+/// Cancel the running search, if any
+#[tauri::command]
+pub fn cancel_search(state: State<AppState>) -> Result<(), String> {
+    match state.active_search.lock().unwrap().take() {
+        Some(search) => {
+            search.cancelled.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        None => Err("No search in progress".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_text_keeps_ranges_inside_clipped_lines() {
+        let re = Regex::new("ab").unwrap();
+        let line = format!("ab{}ab{}ab", "x".repeat(496), "y".repeat(10));
+        let matches = search_text(&line, &re, 0, 10);
+        let found = &matches[0];
+        assert_eq!(found.column, 1);
+        assert_eq!(found.text.chars().count(), MAX_LINE_CHARS + 1);
+        assert_eq!(found.ranges, vec![(0, 2), (498, 500)]);
+
+        let late = format!("{}ab", "x".repeat(600));
+        let found = &search_text(&late, &re, 0, 10)[0];
+        assert_eq!(found.column, 601);
+        assert!(found.ranges.is_empty());
+    }
+}
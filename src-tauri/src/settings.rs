@@ -0,0 +1,197 @@
+//! Claude Code settings files.
+//!
+//! Settings come from three files, highest precedence first:
+//! `.claude/settings.local.json` (personal, usually git-ignored),
+//! `.claude/settings.json` (shared with the team) and
+//! `~/.claude/settings.json` (the user's defaults). Editors load one file as
+//! a JSON object, change only the keys they own and save it back with the
+//! file's own indentation, so unknown keys and key order survive.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+use crate::files::hash_file;
+use crate::safe_write::{self, Expected, WrittenFile};
+use crate::scopes;
+
+/// A settings file and the scope it belongs to
+pub(crate) struct SettingsFile {
+    /// "local", "project" or "user", or "mcp" for the project's `.mcp.json`;
+    /// decides where the file is written and backed up
+    pub scope: &'static str,
+    pub path: PathBuf,
+    project_dir: String,
+}
+
+/// What the frontend needs to know about a settings file
+#[derive(Clone, Serialize)]
+pub struct SettingsFileInfo {
+    pub scope: String,
+    pub path: String,
+    pub exists: bool,
+    /// SHA-256 of the file, to pass back as the expected hash when saving
+    pub hash: Option<String>,
+    /// Why the file couldn't be read, if it couldn't
+    pub error: Option<String>,
+}
+
+/// Payload of `claude-settings-changed`
+#[derive(Clone, Serialize)]
+pub struct SettingsChangedEvent {
+    pub scope: String,
+    pub path: String,
+}
+
+/// A settings file read into memory
+pub(crate) struct Loaded {
+    pub settings: Map<String, Value>,
+    pub info: SettingsFileInfo,
+    /// Indentation to write back with
+    indent: String,
+}
+
+/// Settings files of a project, highest precedence first
+pub(crate) fn settings_files(project_dir: &str) -> Vec<SettingsFile> {
+    ["local", "project", "user"]
+        .iter()
+        .filter_map(|scope| settings_file(project_dir, scope).ok())
+        .collect()
+}
+
+/// The settings file for one scope
+pub(crate) fn settings_file(project_dir: &str, scope: &str) -> Result<SettingsFile, String> {
+    let claude_dir = Path::new(project_dir).join(".claude");
+    let (scope, path) = match scope {
+        "local" => ("local", claude_dir.join("settings.local.json")),
+        "project" => ("project", claude_dir.join("settings.json")),
+        "user" => (
+            "user",
+            scopes::user_claude_dir()
+                .ok_or("Cannot find home directory")?
+                .join("settings.json"),
+        ),
+        other => return Err(format!("Unknown settings scope: {}", other)),
+    };
+    Ok(SettingsFile::at(scope, path, project_dir))
+}
+
+/// Leading whitespace of the first indented line, or two spaces
+fn detect_indent(content: &str) -> String {
+    content
+        .lines()
+        .skip(1)
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("  ")
+        .to_string()
+}
+
+impl SettingsFile {
+    /// Any other JSON config file handled the same way, like `.mcp.json`
+    pub(crate) fn at(scope: &'static str, path: PathBuf, project_dir: &str) -> Self {
+        SettingsFile {
+            scope,
+            path,
+            project_dir: project_dir.to_string(),
+        }
+    }
+
+    /// Read the file. A missing file is an empty object; an unreadable one
+    /// is reported through `info.error`.
+    pub(crate) fn load(&self) -> Loaded {
+        let mut info = SettingsFileInfo {
+            scope: self.scope.to_string(),
+            path: self.path.to_string_lossy().to_string(),
+            exists: self.path.is_file(),
+            hash: None,
+            error: None,
+        };
+        let mut loaded = Loaded {
+            settings: Map::new(),
+            info: info.clone(),
+            indent: "  ".to_string(),
+        };
+        if !info.exists {
+            return loaded;
+        }
+        info.hash = hash_file(&self.path).ok();
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) if content.trim().is_empty() => {}
+            Ok(content) => match serde_json::from_str::<Value>(&content) {
+                Ok(Value::Object(settings)) => {
+                    loaded.settings = settings;
+                    loaded.indent = detect_indent(&content);
+                }
+                Ok(_) => info.error = Some("Settings file is not a JSON object".to_string()),
+                Err(e) => info.error = Some(format!("Invalid JSON: {}", e)),
+            },
+            Err(e) => info.error = Some(e.to_string()),
+        }
+        loaded.info = info;
+        loaded
+    }
+
+    /// Load the file for editing, refusing one that can't be parsed
+    pub(crate) fn load_for_edit(&self) -> Result<Loaded, String> {
+        let loaded = self.load();
+        match &loaded.info.error {
+            Some(error) => Err(format!(
+                "Fix {} before editing it: {}",
+                loaded.info.path, error
+            )),
+            None => Ok(loaded),
+        }
+    }
+
+    /// Save edited settings if the file still matches `expected`
+    pub(crate) fn save(
+        &self,
+        app: &AppHandle,
+        loaded: &Loaded,
+        expected: &Expected,
+    ) -> Result<WrittenFile, String> {
+        let mut content = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(loaded.indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+        serde::Serialize::serialize(&loaded.settings, &mut serializer)
+            .map_err(|e| e.to_string())?;
+        content.push(b'\n');
+
+        let written = match self.scope {
+            "user" => {
+                let name = self
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                safe_write::write_file(
+                    &self.path,
+                    &name,
+                    &content,
+                    expected,
+                    &safe_write::backup_root("user")?,
+                )?
+            }
+            "local" | "project" | "mcp" => {
+                let relative = self
+                    .path
+                    .strip_prefix(&self.project_dir)
+                    .map_err(|e| e.to_string())?
+                    .to_string_lossy()
+                    .to_string();
+                safe_write::write_in_project(app, &self.project_dir, &relative, &content, expected)?
+            }
+            other => return Err(format!("Unknown settings scope: {}", other)),
+        };
+        let _ = app.emit(
+            "claude-settings-changed",
+            SettingsChangedEvent {
+                scope: self.scope.to_string(),
+                path: self.path.to_string_lossy().to_string(),
+            },
+        );
+        Ok(written)
+    }
+}
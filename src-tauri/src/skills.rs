@@ -0,0 +1,239 @@
+//! Skill discovery under `.claude/skills/`.
+//!
+//! Two layouts are recognised: the standard directory skill
+//! (`<name>/SKILL.md` plus any scripts and resources it bundles) and the
+//! older single file (`<name>.md`). Skills that can't be loaded as written
+//! are still listed, with `problems` explaining what is wrong, so the UI can
+//! point at them instead of silently dropping them. Project, user and plugin
+//! skills are merged as described in `scopes`.
+
+use serde::Serialize;
+use std::path::Path;
+use tauri::State;
+
+use crate::frontmatter::{self, ExtraKeys, ParseError, SkillFrontmatter};
+use crate::scopes::{self, ConfigRoot, Origin};
+use crate::{current_project_dir, walk, AppState};
+
+/// Entry file of a directory skill
+pub(crate) const SKILL_FILE: &str = "SKILL.md";
+/// Bundled files listed per skill
+const MAX_BUNDLED_FILES: usize = 200;
+
+/// Skill metadata parsed from YAML frontmatter
+#[derive(Clone, Serialize)]
+pub struct SkillInfo {
+    /// Name to invoke the skill by; prefixed with the plugin for plugin skills
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    /// "directory" or "file"
+    pub layout: String,
+    /// The skill's markdown file, relative to the skills directory
+    pub path: String,
+    /// Other files in a directory skill, relative to the skill directory
+    pub files: Vec<String>,
+    pub allowed_tools: Option<Vec<String>>,
+    pub argument_hint: Option<String>,
+    pub model: Option<String>,
+    pub license: Option<String>,
+    pub version: Option<String>,
+    pub disable_model_invocation: bool,
+    pub extra: ExtraKeys,
+    /// Why the frontmatter couldn't be read, if it couldn't
+    pub error: Option<ParseError>,
+    /// Layout problems that keep Claude from loading the skill as intended
+    pub problems: Vec<String>,
+    #[serde(flatten)]
+    pub origin: Origin,
+}
+
+impl SkillInfo {
+    fn new(root: &ConfigRoot, slug: &str, layout: &str, path: String, source: &Path) -> Self {
+        SkillInfo {
+            name: slug.to_string(),
+            slug: root.qualify(slug),
+            description: String::new(),
+            layout: layout.to_string(),
+            path,
+            files: Vec::new(),
+            allowed_tools: None,
+            argument_hint: None,
+            model: None,
+            license: None,
+            version: None,
+            disable_model_invocation: false,
+            extra: ExtraKeys::new(),
+            error: None,
+            problems: Vec::new(),
+            origin: root.origin(source),
+        }
+    }
+
+    /// Fill metadata from the skill's markdown. Returns the frontmatter keys
+    /// a directory skill requires but that are missing.
+    fn read(&mut self, content: &str) -> Vec<&'static str> {
+        let (fm, body, error) = frontmatter::parse::<SkillFrontmatter>(content);
+        let mut missing = Vec::new();
+        if fm.name.is_none() {
+            missing.push("name");
+        }
+        if fm.description.is_none() {
+            missing.push("description");
+        }
+        if let Some(name) = fm.name {
+            self.name = name;
+        }
+        // Fall back to the first line of the body
+        self.description = fm
+            .description
+            .unwrap_or_else(|| frontmatter::first_body_line(body));
+        self.allowed_tools = fm.allowed_tools;
+        self.argument_hint = fm.argument_hint;
+        self.model = fm.model;
+        self.license = fm.license;
+        self.version = fm.version;
+        self.disable_model_invocation = fm.disable_model_invocation.unwrap_or(false);
+        self.extra = fm.extra;
+        self.error = error;
+        if body.trim().is_empty() {
+            self.problems
+                .push("Skill has no instructions after the frontmatter".to_string());
+        }
+        missing
+    }
+
+    /// A skill that exists on disk and can be loaded
+    pub(crate) fn is_valid(&self) -> bool {
+        self.problems.is_empty() && self.error.is_none()
+    }
+}
+
+/// Files bundled with a directory skill, excluding `SKILL.md` itself
+fn bundled_files(skill_dir: &Path) -> Vec<String> {
+    let mut files: Vec<String> = walk::walker(skill_dir)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| walk::relative_path(skill_dir, entry.path()))
+        .filter(|path| path != SKILL_FILE)
+        .take(MAX_BUNDLED_FILES)
+        .collect();
+    files.sort();
+    files
+}
+
+/// Load a `<name>/SKILL.md` skill
+fn load_directory_skill(root: &ConfigRoot, skill_dir: &Path, slug: String) -> SkillInfo {
+    let entry_file = skill_dir.join(SKILL_FILE);
+    let mut skill = SkillInfo::new(
+        root,
+        &slug,
+        "directory",
+        format!("{}/{}", slug, SKILL_FILE),
+        &entry_file,
+    );
+    skill.files = bundled_files(skill_dir);
+
+    if !entry_file.is_file() {
+        // A differently-cased SKILL.md is found on macOS/Windows but not Linux
+        let misnamed = skill
+            .files
+            .iter()
+            .find(|f| f.eq_ignore_ascii_case(SKILL_FILE))
+            .cloned();
+        match misnamed {
+            Some(found) => {
+                skill
+                    .problems
+                    .push(format!("Rename {} to {}", found, SKILL_FILE));
+                skill.path = format!("{}/{}", slug, found);
+                skill.origin.source_path = skill_dir.join(&found).to_string_lossy().to_string();
+                let content = std::fs::read_to_string(skill_dir.join(&found)).unwrap_or_default();
+                let _ = skill.read(&content);
+            }
+            None => {
+                skill.origin.source_path = skill_dir.to_string_lossy().to_string();
+                skill
+                    .problems
+                    .push(format!("Directory has no {}", SKILL_FILE));
+            }
+        }
+        return skill;
+    }
+
+    match std::fs::read_to_string(&entry_file) {
+        Ok(content) => {
+            // Without these Claude can't tell when to use the skill
+            let missing = skill.read(&content);
+            if skill.error.is_none() && !missing.is_empty() {
+                skill.problems.push(format!(
+                    "{} frontmatter is missing: {}",
+                    SKILL_FILE,
+                    missing.join(", ")
+                ));
+            }
+        }
+        Err(e) => skill
+            .problems
+            .push(format!("Cannot read {}: {}", SKILL_FILE, e)),
+    }
+    skill
+}
+
+/// All skills in one root's `skills/` directory, sorted by slug
+pub(crate) fn scan(root: &ConfigRoot) -> Vec<SkillInfo> {
+    let skills_dir = root.dir.join("skills");
+    let mut skills = Vec::new();
+    let Ok(entries) = std::fs::read_dir(&skills_dir) else {
+        return skills;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            skills.push(load_directory_skill(root, &path, file_name));
+        } else if let Some(slug) = file_name.strip_suffix(".md") {
+            let mut skill = SkillInfo::new(root, slug, "file", file_name.clone(), &path);
+            match std::fs::read_to_string(&path) {
+                Ok(content) => {
+                    let _ = skill.read(&content);
+                }
+                Err(e) => skill
+                    .problems
+                    .push(format!("Cannot read {}: {}", file_name, e)),
+            }
+            skills.push(skill);
+        }
+    }
+
+    // A directory skill wins over a single-file skill of the same name
+    let directories: Vec<String> = skills
+        .iter()
+        .filter(|s| s.layout == "directory")
+        .map(|s| s.slug.clone())
+        .collect();
+    for skill in skills.iter_mut() {
+        if skill.layout == "file" && directories.contains(&skill.slug) {
+            skill.problems.push(format!(
+                "Shadowed by the directory skill {}/{}",
+                skill.slug, SKILL_FILE
+            ));
+        }
+    }
+
+    skills.sort_by(|a, b| a.slug.cmp(&b.slug).then_with(|| a.layout.cmp(&b.layout)));
+    skills
+}
+
+/// Discover project, user and plugin skills with metadata
+#[tauri::command]
+pub fn discover_skills(state: State<AppState>) -> Result<Vec<SkillInfo>, String> {
+    let dir = current_project_dir(&state)?;
+    let mut skills: Vec<SkillInfo> = scopes::config_roots(&dir).iter().flat_map(scan).collect();
+    scopes::mark_shadowed(&mut skills, |s| s.slug.clone(), |s| &mut s.origin);
+    Ok(skills)
+}
@@ -0,0 +1,251 @@
+//! Custom slash commands from `.claude/commands/`.
+//!
+//! Every `*.md` file below the directory is a command named after the file.
+//! Subdirectories namespace a command (`frontend/component.md` is
+//! `/component`, shown as `project:frontend`) without changing its name,
+//! as in the CLI. The body is the prompt template: `$ARGUMENTS` is replaced
+//! by everything typed after the command and `$1`, `$2`, … by single
+//! arguments. Project, user and plugin commands are merged as described in
+//! `scopes`.
+
+use regex::Regex;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::OnceLock;
+use tauri::State;
+
+use crate::frontmatter::{self, ExtraKeys, ParseError, SkillFrontmatter};
+use crate::scopes::{self, ConfigRoot, Origin};
+use crate::{current_project_dir, walk, AppState};
+
+/// A slash command and its frontmatter
+#[derive(Clone, Serialize)]
+pub struct CommandInfo {
+    /// What follows the `/`; prefixed with the plugin for plugin commands
+    pub name: String,
+    /// Subdirectory path joined with `:`, e.g. `frontend:forms`
+    pub namespace: Option<String>,
+    pub description: String,
+    pub argument_hint: Option<String>,
+    pub allowed_tools: Option<Vec<String>>,
+    pub model: Option<String>,
+    pub disable_model_invocation: bool,
+    /// Relative to the commands directory
+    pub path: String,
+    pub extra: ExtraKeys,
+    pub error: Option<ParseError>,
+    #[serde(flatten)]
+    pub origin: Origin,
+}
+
+/// Result of `expand_command`: the prompt to send plus the command's settings
+#[derive(Clone, Serialize)]
+pub struct ExpandedCommand {
+    pub name: String,
+    pub prompt: String,
+    pub model: Option<String>,
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+fn load(root: &ConfigRoot, commands_dir: &Path, path: &Path) -> Option<(CommandInfo, String)> {
+    let relative = walk::relative_path(commands_dir, path);
+    let name = root.qualify(&path.file_stem()?.to_string_lossy());
+    let namespace = Path::new(&relative)
+        .parent()
+        .map(|p| p.to_string_lossy().replace(['/', '\\'], ":"))
+        .filter(|ns| !ns.is_empty());
+    let content = std::fs::read_to_string(path).ok()?;
+    let (fm, body, error) = frontmatter::parse::<SkillFrontmatter>(&content);
+    let info = CommandInfo {
+        name,
+        namespace,
+        description: fm
+            .description
+            .unwrap_or_else(|| frontmatter::first_body_line(body)),
+        argument_hint: fm.argument_hint,
+        allowed_tools: fm.allowed_tools,
+        model: fm.model,
+        disable_model_invocation: fm.disable_model_invocation.unwrap_or(false),
+        path: relative,
+        extra: fm.extra,
+        error,
+        origin: root.origin(path),
+    };
+    Some((info, body.to_string()))
+}
+
+/// All commands under one root's `commands/` directory with their bodies
+pub(crate) fn scan(root: &ConfigRoot) -> Vec<(CommandInfo, String)> {
+    let commands_dir = root.dir.join("commands");
+    if !commands_dir.is_dir() {
+        return Vec::new();
+    }
+    let mut commands: Vec<(CommandInfo, String)> = walk::walker(&commands_dir)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "md"))
+        .filter_map(|entry| load(root, &commands_dir, entry.path()))
+        .collect();
+    commands.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    commands
+}
+
+/// Split arguments like a shell would: whitespace-separated, with quotes
+/// grouping words and backslash escaping the next character
+fn split_args(args: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = args.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (q, '\\') if q != Some('\'') => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    parts.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (_, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Fill a command body's placeholders with `args`
+pub(crate) fn expand(body: &str, args: &str) -> String {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    let placeholder = PLACEHOLDER.get_or_init(|| Regex::new(r"\$(ARGUMENTS|\d+)").unwrap());
+    let args = args.trim();
+    let positional = split_args(args);
+
+    let has_placeholder = placeholder.is_match(body);
+    let expanded = placeholder.replace_all(body, |caps: &regex::Captures| match &caps[1] {
+        "ARGUMENTS" => args.to_string(),
+        index => index
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| i.checked_sub(1))
+            .and_then(|i| positional.get(i))
+            .cloned()
+            .unwrap_or_default(),
+    });
+    let expanded = expanded.trim().to_string();
+
+    // The CLI appends arguments a template has no place for
+    if !has_placeholder && !args.is_empty() {
+        format!("{}\n\nARGUMENTS: {}", expanded, args)
+    } else {
+        expanded
+    }
+}
+
+/// Commands from every scope, in precedence order, with shadowing marked
+fn scan_all(project_dir: &str) -> Vec<(CommandInfo, String)> {
+    let mut commands: Vec<(CommandInfo, String)> = scopes::config_roots(project_dir)
+        .iter()
+        .flat_map(scan)
+        .collect();
+    // Namespaces don't take part in the name, so they don't prevent clashes
+    scopes::mark_shadowed(
+        &mut commands,
+        |(info, _)| info.name.clone(),
+        |(info, _)| &mut info.origin,
+    );
+    commands
+}
+
+/// Discover project, user and plugin slash commands
+#[tauri::command]
+pub fn discover_commands(state: State<AppState>) -> Result<Vec<CommandInfo>, String> {
+    let dir = current_project_dir(&state)?;
+    Ok(scan_all(&dir).into_iter().map(|(info, _)| info).collect())
+}
+
+/// Turn `/name args` into the prompt the CLI would send. `name` may be
+/// qualified with its namespace (`frontend:component`) to pick a command
+/// other than the one that takes precedence.
+#[tauri::command]
+pub fn expand_command(
+    state: State<AppState>,
+    name: String,
+    args: Option<String>,
+) -> Result<ExpandedCommand, String> {
+    let dir = current_project_dir(&state)?;
+    let name = name.trim().trim_start_matches('/');
+    let mut commands = scan_all(&dir);
+    // Exact names first (plugin commands contain `:` themselves), winners first
+    commands.sort_by_key(|(info, _)| info.origin.shadowed_by.is_some());
+    let exact = commands.iter().position(|(info, _)| info.name == name);
+    let found = exact.or_else(|| {
+        let (namespace, short_name) = name.rsplit_once(':')?;
+        commands.iter().position(|(info, _)| {
+            info.name == short_name && info.namespace.as_deref() == Some(namespace)
+        })
+    });
+    let (info, body) = found
+        .map(|index| commands.swap_remove(index))
+        .ok_or_else(|| format!("Unknown command: /{}", name))?;
+    if let Some(error) = &info.error {
+        return Err(format!(
+            "/{} has invalid frontmatter: {}",
+            name, error.message
+        ));
+    }
+    Ok(ExpandedCommand {
+        prompt: expand(&body, args.as_deref().unwrap_or("")),
+        name: info.name,
+        model: info.model,
+        allowed_tools: info.allowed_tools,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_args_groups_quotes_and_escapes() {
+        assert_eq!(
+            split_args(r#"one "two three" 'four \five' six\ seven"#),
+            vec!["one", "two three", r"four \five", "six seven"]
+        );
+        assert_eq!(split_args(r#"a "" b"#), vec!["a", "", "b"]);
+        assert!(split_args("   ").is_empty());
+    }
+
+    #[test]
+    fn expand_fills_placeholders() {
+        assert_eq!(
+            expand("Fix $1 in $2 ($ARGUMENTS); $3", r#" bug "src/a b.rs" "#),
+            r#"Fix bug in src/a b.rs (bug "src/a b.rs");"#
+        );
+    }
+
+    #[test]
+    fn expand_appends_arguments_without_placeholders() {
+        assert_eq!(
+            expand("Review the diff\n", "carefully"),
+            "Review the diff\n\nARGUMENTS: carefully"
+        );
+        assert_eq!(expand("Review the diff\n", "  "), "Review the diff");
+    }
+}
@@ -0,0 +1,17 @@
+//! Fixtures shared by the unit tests.
+
+use std::path::PathBuf;
+
+/// A fresh project tree holding `files`, in a temporary directory named
+/// after the test so tests running in parallel never share one
+pub(crate) fn project(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("cc-desktop-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    for (path, content) in files {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    root
+}
@@ -0,0 +1,754 @@
+//! How a project is checked: test runners, linters, formatters and type
+//! checkers, CI pipelines, container files and pre-commit hooks.
+//!
+//! Every tool comes with the exact command that runs it here: the project's
+//! own package.json script when one runs the tool, otherwise the tool
+//! through the project's package manager or runner. Agents use these
+//! commands to verify their work the way CI would.
+
+use serde::Serialize;
+use serde_json::Value;
+use serde_yaml::Value as Yaml;
+use std::path::Path;
+
+use crate::manifests::{self, Manifest};
+use crate::walk;
+
+/// How deep to look for Dockerfiles and compose files
+const CONTAINER_DEPTH: usize = 3;
+
+const COMPOSE_FILES: &[&str] = &[
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// Top-level .gitlab-ci.yml keys that aren't jobs
+const GITLAB_KEYWORDS: &[&str] = &[
+    "default",
+    "include",
+    "stages",
+    "variables",
+    "workflow",
+    "image",
+    "services",
+    "cache",
+    "before_script",
+    "after_script",
+];
+
+/// A test runner, linter, formatter or type checker
+#[derive(Clone, Serialize)]
+pub struct Tool {
+    pub name: String,
+    /// "test", "lint", "format" or "typecheck"
+    pub kind: String,
+    /// Runs the tool without changing files
+    pub command: String,
+    /// Fixes what the tool can fix, if it can
+    pub fix_command: Option<String>,
+    /// Config file or manifest that gave it away
+    pub source: String,
+}
+
+/// A CI job and the shell commands it runs
+#[derive(Clone, Serialize)]
+pub struct CiJob {
+    pub name: String,
+    /// GitLab stage
+    pub stage: Option<String>,
+    pub commands: Vec<String>,
+}
+
+/// A CI pipeline definition
+#[derive(Clone, Serialize)]
+pub struct CiConfig {
+    /// "github-actions" or "gitlab-ci"
+    pub provider: String,
+    pub path: String,
+    pub name: Option<String>,
+    /// Events that start it, e.g. "push", "pull_request"
+    pub triggers: Vec<String>,
+    pub jobs: Vec<CiJob>,
+    /// Why the file couldn't be parsed, if it couldn't
+    pub error: Option<String>,
+}
+
+/// A Dockerfile or compose file
+#[derive(Clone, Serialize)]
+pub struct ContainerFile {
+    /// "dockerfile" or "compose"
+    pub kind: String,
+    pub path: String,
+    /// Base images of a Dockerfile, images of a compose file's services
+    pub images: Vec<String>,
+    /// Services of a compose file
+    pub services: Vec<String>,
+    pub command: String,
+}
+
+/// pre-commit hooks
+#[derive(Clone, Serialize)]
+pub struct PreCommit {
+    pub path: String,
+    /// Hook ids, in the order they run
+    pub hooks: Vec<String>,
+    pub command: String,
+    pub install_command: String,
+}
+
+/// Everything that checks the project
+#[derive(Clone, Serialize)]
+pub struct Tooling {
+    pub tools: Vec<Tool>,
+    pub ci: Vec<CiConfig>,
+    pub containers: Vec<ContainerFile>,
+    pub pre_commit: Option<PreCommit>,
+}
+
+fn tool(name: &str, kind: &str, command: String, fix: Option<String>, source: &str) -> Tool {
+    Tool {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        command,
+        fix_command: fix,
+        source: source.to_string(),
+    }
+}
+
+/// The first of `names` that exists in `root`
+fn find_file<'a>(root: &Path, names: &[&'a str]) -> Option<&'a str> {
+    names.iter().copied().find(|name| root.join(name).exists())
+}
+
+/// Config file names for each extension a JavaScript config may have
+fn js_configs(stem: &str) -> Vec<String> {
+    ["js", "mjs", "cjs", "ts", "mts", "cts", "json"]
+        .iter()
+        .map(|ext| format!("{}.{}", stem, ext))
+        .collect()
+}
+
+fn find_config(root: &Path, names: &[String]) -> Option<String> {
+    names.iter().find(|name| root.join(name).exists()).cloned()
+}
+
+/// How the package manager runs a locally installed binary
+fn exec_bin(manager: &str, bin: &str) -> String {
+    match manager {
+        "pnpm" => format!("pnpm exec {}", bin),
+        "yarn" => format!("yarn {}", bin),
+        "bun" => format!("bunx {}", bin),
+        _ => format!("npx {}", bin),
+    }
+}
+
+/// Words of a script command, split on whitespace and shell operators
+fn command_words(command: &str) -> Vec<&str> {
+    command
+        .split(|c: char| c.is_whitespace() || c == '&' || c == ';' || c == '|')
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Whether a command passes any of `flags`
+fn passes(words: &[&str], flags: &[&str]) -> bool {
+    words.iter().any(|word| flags.contains(word))
+}
+
+/// JavaScript tools, through package.json scripts where they exist. Checks
+/// only use scripts that leave files alone; fixes only ones that write.
+fn node_tools(root: &Path, manifest: &Manifest, tools: &mut Vec<Tool>) {
+    let manager = manifests::node_package_manager(root);
+    let package: Value = std::fs::read_to_string(root.join("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or(Value::Null);
+    let has_dep = |name: &str| manifest.dependencies.iter().any(|d| d.name == name);
+    // The script that runs `bin` in a way `accept` allows, preferring one
+    // named like the kind
+    let script = |bin: &str, preferred: &[&str], accept: &dyn Fn(&[&str]) -> bool| {
+        let scripts: Vec<&manifests::Script> = manifest
+            .scripts
+            .iter()
+            .filter(|s| {
+                let words = command_words(&s.command);
+                words.contains(&bin) && accept(&words)
+            })
+            .collect();
+        scripts
+            .iter()
+            .find(|s| preferred.contains(&s.name.as_str()))
+            .or(scripts.first())
+            .map(|s| manifests::run_script(manager, &s.name))
+    };
+    let any = |_: &[&str]| true;
+
+    let jest_config = find_config(root, &js_configs("jest.config"));
+    if has_dep("jest") || jest_config.is_some() || package.get("jest").is_some() {
+        tools.push(tool(
+            "Jest",
+            "test",
+            script("jest", &["test"], &any).unwrap_or_else(|| exec_bin(manager, "jest")),
+            None,
+            jest_config.as_deref().unwrap_or("package.json"),
+        ));
+    }
+    let vitest_config = find_config(root, &js_configs("vitest.config"));
+    if has_dep("vitest") || vitest_config.is_some() {
+        // A bare `vitest` watches; only a script that already runs once will do
+        let command = script("vitest", &["test"], &|words| {
+            passes(words, &["run", "--run"])
+        })
+        .unwrap_or_else(|| exec_bin(manager, "vitest run"));
+        tools.push(tool(
+            "Vitest",
+            "test",
+            command,
+            None,
+            vitest_config.as_deref().unwrap_or("package.json"),
+        ));
+    }
+
+    let mut eslint_configs = js_configs("eslint.config");
+    eslint_configs.extend(
+        [
+            ".eslintrc",
+            ".eslintrc.js",
+            ".eslintrc.cjs",
+            ".eslintrc.json",
+            ".eslintrc.yml",
+            ".eslintrc.yaml",
+        ]
+        .map(str::to_string),
+    );
+    let eslint_config = find_config(root, &eslint_configs);
+    if has_dep("eslint") || eslint_config.is_some() {
+        let command = script("eslint", &["lint"], &|words| !passes(words, &["--fix"]))
+            .unwrap_or_else(|| exec_bin(manager, "eslint ."));
+        let fix = script("eslint", &["lint:fix", "fix"], &|words| {
+            passes(words, &["--fix"])
+        })
+        .unwrap_or_else(|| exec_bin(manager, "eslint --fix ."));
+        tools.push(tool(
+            "ESLint",
+            "lint",
+            command,
+            Some(fix),
+            eslint_config.as_deref().unwrap_or("package.json"),
+        ));
+    }
+    let mut prettier_configs = js_configs("prettier.config");
+    prettier_configs.extend(
+        [
+            ".prettierrc",
+            ".prettierrc.json",
+            ".prettierrc.js",
+            ".prettierrc.cjs",
+            ".prettierrc.yml",
+            ".prettierrc.yaml",
+        ]
+        .map(str::to_string),
+    );
+    let prettier_config = find_config(root, &prettier_configs);
+    if has_dep("prettier") || prettier_config.is_some() {
+        let command = script(
+            "prettier",
+            &["format:check", "check-format", "lint:format"],
+            &|words| passes(words, &["--check", "-c", "--list-different", "-l"]),
+        )
+        .unwrap_or_else(|| exec_bin(manager, "prettier --check ."));
+        let fix = script("prettier", &["format", "fmt"], &|words| {
+            passes(words, &["--write", "-w"])
+        })
+        .unwrap_or_else(|| exec_bin(manager, "prettier --write ."));
+        tools.push(tool(
+            "Prettier",
+            "format",
+            command,
+            Some(fix),
+            prettier_config.as_deref().unwrap_or("package.json"),
+        ));
+    }
+    let biome_config = find_file(root, &["biome.json", "biome.jsonc"]);
+    if has_dep("@biomejs/biome") || biome_config.is_some() {
+        const BIOME_WRITES: &[&str] = &["--write", "--apply", "--apply-unsafe", "--fix"];
+        let command = script("biome", &["lint", "check"], &|words| {
+            !passes(words, BIOME_WRITES)
+        })
+        .unwrap_or_else(|| exec_bin(manager, "biome check ."));
+        let fix = script("biome", &["lint:fix", "fix", "format"], &|words| {
+            passes(words, BIOME_WRITES)
+        })
+        .unwrap_or_else(|| exec_bin(manager, "biome check --write ."));
+        tools.push(tool(
+            "Biome",
+            "lint",
+            command,
+            Some(fix),
+            biome_config.unwrap_or("package.json"),
+        ));
+    }
+    let stylelint_config = find_file(
+        root,
+        &[".stylelintrc", ".stylelintrc.json", "stylelint.config.js"],
+    );
+    if let Some(config) = stylelint_config {
+        let command = script("stylelint", &["lint:css", "stylelint"], &|words| {
+            !passes(words, &["--fix"])
+        })
+        .unwrap_or_else(|| exec_bin(manager, "stylelint \"**/*.css\""));
+        let fix = script("stylelint", &["lint:css:fix", "stylelint:fix"], &|words| {
+            passes(words, &["--fix"])
+        })
+        .unwrap_or_else(|| exec_bin(manager, "stylelint --fix \"**/*.css\""));
+        tools.push(tool("Stylelint", "lint", command, Some(fix), config));
+    }
+    if root.join("tsconfig.json").exists() {
+        let command = script("tsc", &["typecheck", "type-check", "check-types"], &any)
+            .unwrap_or_else(|| exec_bin(manager, "tsc --noEmit"));
+        tools.push(tool(
+            "TypeScript",
+            "typecheck",
+            command,
+            None,
+            "tsconfig.json",
+        ));
+    }
+}
+
+/// Whether pyproject.toml has a `[section]` or `[section.*]` table
+fn has_section(pyproject: &str, section: &str) -> bool {
+    pyproject.lines().any(|line| {
+        let line = line.trim();
+        line.strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .is_some_and(|name| name == section || name.starts_with(&format!("{}.", section)))
+    })
+}
+
+/// Python tools, run through uv, Poetry, PDM or Pipenv when the project
+/// uses one
+fn python_tools(root: &Path, found: &[Manifest], tools: &mut Vec<Tool>) {
+    let runner = match manifests::package_managers(root, found)
+        .into_iter()
+        .find(|m| ["uv", "poetry", "pdm", "pipenv", "pip"].contains(&m.as_str()))
+    {
+        Some(manager) if manager != "pip" => format!("{} run ", manager),
+        _ => String::new(),
+    };
+    let pyproject = std::fs::read_to_string(root.join("pyproject.toml")).unwrap_or_default();
+    let deps: Vec<&str> = found
+        .iter()
+        .filter(|m| m.ecosystem == "python")
+        .flat_map(|m| m.dependencies.iter().map(|d| d.name.as_str()))
+        .collect();
+    let manifest = found
+        .iter()
+        .find(|m| m.ecosystem == "python")
+        .map(|m| m.path.as_str())
+        .unwrap_or("pyproject.toml");
+    let configured = |section: &str, files: &[&'static str]| -> Option<&'static str> {
+        if has_section(&pyproject, section) {
+            Some("pyproject.toml")
+        } else {
+            find_file(root, files)
+        }
+    };
+    let run = |command: &str| format!("{}{}", runner, command);
+
+    let pytest = configured("tool.pytest", &["pytest.ini", "conftest.py"])
+        .or_else(|| deps.contains(&"pytest").then_some(manifest));
+    if let Some(source) = pytest {
+        tools.push(tool("pytest", "test", run("pytest"), None, source));
+    }
+    let ruff = configured("tool.ruff", &["ruff.toml", ".ruff.toml"])
+        .or_else(|| deps.contains(&"ruff").then_some(manifest));
+    if let Some(source) = ruff {
+        tools.push(tool(
+            "Ruff",
+            "lint",
+            run("ruff check ."),
+            Some(run("ruff check --fix .")),
+            source,
+        ));
+        tools.push(tool(
+            "Ruff",
+            "format",
+            run("ruff format --check ."),
+            Some(run("ruff format .")),
+            source,
+        ));
+    }
+    let black =
+        configured("tool.black", &[]).or_else(|| deps.contains(&"black").then_some(manifest));
+    if let Some(source) = black {
+        tools.push(tool(
+            "Black",
+            "format",
+            run("black --check ."),
+            Some(run("black .")),
+            source,
+        ));
+    }
+    if let Some(source) = find_file(root, &[".flake8"]) {
+        tools.push(tool("Flake8", "lint", run("flake8"), None, source));
+    }
+    let mypy = configured("tool.mypy", &["mypy.ini", ".mypy.ini"])
+        .or_else(|| deps.contains(&"mypy").then_some(manifest));
+    if let Some(source) = mypy {
+        tools.push(tool("mypy", "typecheck", run("mypy ."), None, source));
+    }
+}
+
+fn rust_tools(root: &Path, tools: &mut Vec<Tool>) {
+    let workspace = std::fs::read_to_string(root.join("Cargo.toml"))
+        .ok()
+        .and_then(|c| toml::from_str::<toml::Table>(&c).ok())
+        .is_some_and(|t| t.contains_key("workspace"));
+    let all = if workspace { " --workspace" } else { "" };
+    tools.push(tool(
+        "cargo test",
+        "test",
+        format!("cargo test{}", all),
+        None,
+        "Cargo.toml",
+    ));
+    tools.push(tool(
+        "Clippy",
+        "lint",
+        format!("cargo clippy{} --all-targets -- -D warnings", all),
+        Some(format!("cargo clippy{} --all-targets --fix", all)),
+        find_file(root, &["clippy.toml", ".clippy.toml"]).unwrap_or("Cargo.toml"),
+    ));
+    tools.push(tool(
+        "rustfmt",
+        "format",
+        "cargo fmt --all -- --check".to_string(),
+        Some("cargo fmt --all".to_string()),
+        find_file(root, &["rustfmt.toml", ".rustfmt.toml"]).unwrap_or("Cargo.toml"),
+    ));
+}
+
+fn go_tools(root: &Path, tools: &mut Vec<Tool>) {
+    tools.push(tool(
+        "go test",
+        "test",
+        "go test ./...".to_string(),
+        None,
+        "go.mod",
+    ));
+    match find_file(root, &[".golangci.yml", ".golangci.yaml", ".golangci.toml"]) {
+        Some(config) => tools.push(tool(
+            "golangci-lint",
+            "lint",
+            "golangci-lint run".to_string(),
+            Some("golangci-lint run --fix".to_string()),
+            config,
+        )),
+        None => tools.push(tool(
+            "go vet",
+            "lint",
+            "go vet ./...".to_string(),
+            None,
+            "go.mod",
+        )),
+    }
+    tools.push(tool(
+        "gofmt",
+        "format",
+        // `gofmt -l` lists unformatted files but exits 0 either way
+        "test -z \"$(gofmt -l .)\"".to_string(),
+        Some("gofmt -w .".to_string()),
+        "go.mod",
+    ));
+}
+
+fn yaml_strings(value: Option<&Yaml>) -> Vec<String> {
+    match value {
+        Some(Yaml::String(s)) => vec![s.clone()],
+        Some(Yaml::Sequence(items)) => items
+            .iter()
+            .filter_map(Yaml::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn yaml_keys(value: Option<&Yaml>) -> Vec<String> {
+    value
+        .and_then(Yaml::as_mapping)
+        .map(|map| {
+            map.keys()
+                .filter_map(Yaml::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_yaml(path: &Path) -> Result<Yaml, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_yaml::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn github_workflow(root: &Path, path: &Path) -> CiConfig {
+    let mut config = CiConfig {
+        provider: "github-actions".to_string(),
+        path: walk::relative_path(root, path),
+        name: None,
+        triggers: Vec::new(),
+        jobs: Vec::new(),
+        error: None,
+    };
+    let workflow = match read_yaml(path) {
+        Ok(workflow) => workflow,
+        Err(e) => {
+            config.error = Some(e);
+            return config;
+        }
+    };
+    config.name = workflow
+        .get("name")
+        .and_then(Yaml::as_str)
+        .map(str::to_string);
+    config.triggers = match workflow.get("on") {
+        Some(Yaml::Mapping(_)) => yaml_keys(workflow.get("on")),
+        other => yaml_strings(other),
+    };
+    let jobs = workflow.get("jobs").and_then(Yaml::as_mapping);
+    for (id, job) in jobs.into_iter().flatten() {
+        let Some(id) = id.as_str() else {
+            continue;
+        };
+        let steps = job.get("steps").and_then(Yaml::as_sequence);
+        config.jobs.push(CiJob {
+            name: job
+                .get("name")
+                .and_then(Yaml::as_str)
+                .unwrap_or(id)
+                .to_string(),
+            stage: None,
+            commands: steps
+                .into_iter()
+                .flatten()
+                .filter_map(|step| step.get("run").and_then(Yaml::as_str))
+                .map(|run| run.trim().to_string())
+                .collect(),
+        });
+    }
+    config
+}
+
+fn gitlab_ci(root: &Path, path: &Path) -> CiConfig {
+    let mut config = CiConfig {
+        provider: "gitlab-ci".to_string(),
+        path: walk::relative_path(root, path),
+        name: None,
+        triggers: Vec::new(),
+        jobs: Vec::new(),
+        error: None,
+    };
+    let pipeline = match read_yaml(path) {
+        Ok(pipeline) => pipeline,
+        Err(e) => {
+            config.error = Some(e);
+            return config;
+        }
+    };
+    for (name, job) in pipeline.as_mapping().into_iter().flatten() {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        // Keys starting with a dot are templates, not jobs
+        if name.starts_with('.') || GITLAB_KEYWORDS.contains(&name) || !job.is_mapping() {
+            continue;
+        }
+        config.jobs.push(CiJob {
+            name: name.to_string(),
+            stage: job.get("stage").and_then(Yaml::as_str).map(str::to_string),
+            commands: yaml_strings(job.get("script")),
+        });
+    }
+    config
+}
+
+fn ci_configs(root: &Path) -> Vec<CiConfig> {
+    let mut configs = Vec::new();
+    let mut workflows: Vec<_> = std::fs::read_dir(root.join(".github").join("workflows"))
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    workflows.retain(|path| {
+        path.extension()
+            .is_some_and(|ext| ext == "yml" || ext == "yaml")
+    });
+    workflows.sort();
+    configs.extend(workflows.iter().map(|path| github_workflow(root, path)));
+    let gitlab = root.join(".gitlab-ci.yml");
+    if gitlab.is_file() {
+        configs.push(gitlab_ci(root, &gitlab));
+    }
+    configs
+}
+
+fn is_dockerfile(name: &str) -> bool {
+    name == "Dockerfile"
+        || name == "Containerfile"
+        || name.starts_with("Dockerfile.")
+        || name.ends_with(".Dockerfile")
+        || name.ends_with(".dockerfile")
+}
+
+/// Base images from `FROM` lines, skipping references to earlier stages
+fn base_images(content: &str) -> Vec<String> {
+    let mut stages: Vec<String> = Vec::new();
+    let mut images: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let mut words = line.split_whitespace();
+        if !words.next().is_some_and(|w| w.eq_ignore_ascii_case("FROM")) {
+            continue;
+        }
+        let rest: Vec<&str> = words.filter(|w| !w.starts_with("--")).collect();
+        let Some(image) = rest.first() else {
+            continue;
+        };
+        if !stages.iter().any(|s| s.eq_ignore_ascii_case(image))
+            && !images.iter().any(|i| i == image)
+        {
+            images.push(image.to_string());
+        }
+        if rest.len() >= 3 && rest[1].eq_ignore_ascii_case("AS") {
+            stages.push(rest[2].to_string());
+        }
+    }
+    images
+}
+
+fn containers(root: &Path) -> Vec<ContainerFile> {
+    let mut files: Vec<(String, bool)> = walk::walker(root)
+        .max_depth(Some(CONTAINER_DEPTH))
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let compose = COMPOSE_FILES.contains(&name.as_str());
+            (compose || is_dockerfile(&name))
+                .then(|| (walk::relative_path(root, entry.path()), compose))
+        })
+        .collect();
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|(path, compose)| {
+            let absolute = root.join(&path);
+            if compose {
+                let services = read_yaml(&absolute)
+                    .ok()
+                    .and_then(|c| c.get("services").cloned());
+                let images = services
+                    .as_ref()
+                    .and_then(Yaml::as_mapping)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(_, service)| service.get("image").and_then(Yaml::as_str))
+                    .map(str::to_string)
+                    .collect();
+                // Compose finds any of these names in the working directory
+                let command = if path.contains('/') {
+                    format!("docker compose -f {} up", path)
+                } else {
+                    "docker compose up".to_string()
+                };
+                ContainerFile {
+                    kind: "compose".to_string(),
+                    images,
+                    services: yaml_keys(services.as_ref()),
+                    command,
+                    path,
+                }
+            } else {
+                let content = std::fs::read_to_string(&absolute).unwrap_or_default();
+                let context = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(".");
+                let command = if path == "Dockerfile" {
+                    "docker build .".to_string()
+                } else {
+                    format!("docker build -f {} {}", path, context)
+                };
+                ContainerFile {
+                    kind: "dockerfile".to_string(),
+                    images: base_images(&content),
+                    services: Vec::new(),
+                    command,
+                    path,
+                }
+            }
+        })
+        .collect()
+}
+
+fn pre_commit(root: &Path) -> Option<PreCommit> {
+    let path = ".pre-commit-config.yaml";
+    let config = read_yaml(&root.join(path)).ok()?;
+    let repos = config.get("repos").and_then(Yaml::as_sequence);
+    let hooks = repos
+        .into_iter()
+        .flatten()
+        .filter_map(|repo| repo.get("hooks").and_then(Yaml::as_sequence))
+        .flatten()
+        .filter_map(|hook| hook.get("id").and_then(Yaml::as_str))
+        .map(str::to_string)
+        .collect();
+    Some(PreCommit {
+        path: path.to_string(),
+        hooks,
+        command: "pre-commit run --all-files".to_string(),
+        install_command: "pre-commit install".to_string(),
+    })
+}
+
+/// Tooling of the project at `root`, with `found` as read by
+/// `manifests::read_dir`
+pub(crate) fn detect(root: &Path, found: &[Manifest]) -> Tooling {
+    let mut tools = Vec::new();
+    if let Some(package) = found.iter().find(|m| m.ecosystem == "node") {
+        node_tools(root, package, &mut tools);
+    }
+    if found.iter().any(|m| m.ecosystem == "python") {
+        python_tools(root, found, &mut tools);
+    }
+    if found.iter().any(|m| m.ecosystem == "rust") {
+        rust_tools(root, &mut tools);
+    }
+    if found.iter().any(|m| m.ecosystem == "go") {
+        go_tools(root, &mut tools);
+    }
+    Tooling {
+        tools,
+        ci: ci_configs(root),
+        containers: containers(root),
+        pre_commit: pre_commit(root),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_images_skip_stages_and_flags() {
+        let dockerfile = "\
+FROM --platform=linux/amd64 node:20 AS build
+RUN npm ci
+from build AS test
+FROM node:20
+FROM nginx:alpine
+COPY --from=build /app/dist /usr/share/nginx/html
+";
+        assert_eq!(base_images(dockerfile), vec!["node:20", "nginx:alpine"]);
+        assert!(base_images("# FROM nothing\nRUN true\n").is_empty());
+    }
+}
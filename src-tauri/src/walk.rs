@@ -0,0 +1,107 @@
+//! Ignore-aware project traversal shared by everything that scans the tree.
+//!
+//! Honours `.gitignore`, `.ignore`, `.git/info/exclude` and the user's global
+//! git excludes, even outside a git repository. Dotfiles are included (agents
+//! edit `.claude/` and friends) but the `.git` directory itself never is.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{Match, WalkBuilder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Walker over `root` with the app's standard ignore rules
+pub(crate) fn walker(root: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .ignore(true)
+        .parents(true)
+        .require_git(false)
+        .follow_links(false)
+        .filter_entry(|entry| entry.file_name() != ".git");
+    builder
+}
+
+/// Project-relative path with forward slashes, as the frontend expects
+pub(crate) fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Heuristic binary check: a NUL byte in the first 8 KiB
+pub(crate) fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8192).any(|&b| b == 0)
+}
+
+/// Answers "would the walker skip this path?" for single paths, e.g. from
+/// filesystem events. Ignore files are loaded lazily per directory and cached.
+pub(crate) struct IgnoreMatcher {
+    root: PathBuf,
+    global: Gitignore,
+    per_dir: HashMap<PathBuf, Gitignore>,
+}
+
+impl IgnoreMatcher {
+    pub(crate) fn new(root: &Path) -> Self {
+        let (global, _) = GitignoreBuilder::new(root).build_global();
+        IgnoreMatcher {
+            root: root.to_path_buf(),
+            global,
+            per_dir: HashMap::new(),
+        }
+    }
+
+    /// Matcher for the ignore files that live directly in `dir`
+    fn dir_matcher(&mut self, dir: &Path) -> &Gitignore {
+        let root = &self.root;
+        self.per_dir.entry(dir.to_path_buf()).or_insert_with(|| {
+            let mut builder = GitignoreBuilder::new(dir);
+            if dir == root {
+                let _ = builder.add(dir.join(".git").join("info").join("exclude"));
+            }
+            let _ = builder.add(dir.join(".gitignore"));
+            let _ = builder.add(dir.join(".ignore"));
+            builder.build().unwrap_or_else(|_| Gitignore::empty())
+        })
+    }
+
+    /// Forget cached rules, e.g. after an ignore file changed
+    pub(crate) fn reset(&mut self) {
+        self.per_dir.clear();
+    }
+
+    pub(crate) fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        if relative.components().any(|c| c.as_os_str() == ".git") {
+            return true;
+        }
+
+        // Deeper ignore files take precedence over shallower ones
+        let dirs: Vec<PathBuf> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .map(Path::to_path_buf)
+            .collect();
+        for dir in dirs {
+            match self
+                .dir_matcher(&dir)
+                .matched_path_or_any_parents(path, is_dir)
+            {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        self.global
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+    }
+}
@@ -0,0 +1,203 @@
+//! Live filesystem watcher for the open project.
+//!
+//! Started whenever a project is selected. Raw notifications are debounced,
+//! filtered through the same ignore rules as the file tree, and emitted as
+//! `fs-changed` events. While a run is in progress in the project directory,
+//! events carry its run id so the sidebar can mark the files Claude touches.
+
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::walk::{self, IgnoreMatcher};
+use crate::AppState;
+
+/// Quiet period before a burst of changes is reported
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Handle that keeps the watcher alive; dropping it stops watching
+pub type ProjectWatcher = Debouncer<RecommendedWatcher, RecommendedCache>;
+
+#[derive(Clone, Serialize)]
+pub struct RenamedPath {
+    pub from: String,
+    pub to: String,
+}
+
+/// Payload of the `fs-changed` event. Paths are project-relative.
+#[derive(Clone, Serialize)]
+pub struct FsChangedEvent {
+    /// Run in progress when the changes happened, if any
+    pub run_id: Option<String>,
+    pub created: Vec<String>,
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+    pub renamed: Vec<RenamedPath>,
+}
+
+/// Sorts one debounced batch into created/modified/deleted/renamed sets
+#[derive(Default)]
+struct Batch {
+    created: BTreeSet<PathBuf>,
+    modified: BTreeSet<PathBuf>,
+    deleted: BTreeSet<PathBuf>,
+    renamed: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Batch {
+    fn create(&mut self, path: &Path) {
+        self.deleted.remove(path);
+        self.created.insert(path.to_path_buf());
+    }
+
+    fn delete(&mut self, path: &Path) {
+        // Created and removed within one batch: nothing to report
+        if !self.created.remove(path) {
+            self.deleted.insert(path.to_path_buf());
+        }
+        self.modified.remove(path);
+    }
+
+    fn modify(&mut self, path: &Path) {
+        if !self.created.contains(path) {
+            self.modified.insert(path.to_path_buf());
+        }
+    }
+
+    fn add(&mut self, kind: &EventKind, paths: &[PathBuf]) {
+        match kind {
+            EventKind::Create(_) => paths.iter().for_each(|p| self.create(p)),
+            EventKind::Remove(_) => paths.iter().for_each(|p| self.delete(p)),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                self.renamed.push((paths[0].clone(), paths[1].clone()));
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                paths.iter().for_each(|p| self.delete(p))
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                paths.iter().for_each(|p| self.create(p))
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                for path in paths {
+                    if path.exists() {
+                        self.create(path);
+                    } else {
+                        self.delete(path);
+                    }
+                }
+            }
+            // Permission/timestamp-only changes don't matter to the tree
+            EventKind::Modify(ModifyKind::Metadata(_)) | EventKind::Access(_) => {}
+            EventKind::Modify(_) | EventKind::Any | EventKind::Other => {
+                paths.iter().for_each(|p| self.modify(p))
+            }
+        }
+    }
+}
+
+/// Turn a batch into an event, dropping ignored paths.
+/// Returns `None` when nothing visible changed.
+fn to_event(
+    root: &Path,
+    batch: Batch,
+    matcher: &mut IgnoreMatcher,
+    run_id: Option<String>,
+) -> Option<FsChangedEvent> {
+    let touches_ignore_file = batch
+        .created
+        .iter()
+        .chain(&batch.modified)
+        .chain(&batch.deleted)
+        .chain(batch.renamed.iter().flat_map(|(from, to)| [from, to]))
+        .any(|p| p.ends_with(".gitignore") || p.ends_with(".ignore"));
+    if touches_ignore_file {
+        matcher.reset();
+    }
+
+    let mut visible = |set: BTreeSet<PathBuf>| -> Vec<String> {
+        set.into_iter()
+            .filter(|p| !matcher.is_ignored(p, p.is_dir()))
+            .map(|p| walk::relative_path(root, &p))
+            .collect()
+    };
+    let created = visible(batch.created);
+    let modified = visible(batch.modified);
+    let deleted = visible(batch.deleted);
+    let renamed: Vec<RenamedPath> = batch
+        .renamed
+        .into_iter()
+        .filter(|(from, to)| {
+            !matcher.is_ignored(to, to.is_dir()) || !matcher.is_ignored(from, false)
+        })
+        .map(|(from, to)| RenamedPath {
+            from: walk::relative_path(root, &from),
+            to: walk::relative_path(root, &to),
+        })
+        .collect();
+
+    if created.is_empty() && modified.is_empty() && deleted.is_empty() && renamed.is_empty() {
+        return None;
+    }
+    Some(FsChangedEvent {
+        run_id,
+        created,
+        modified,
+        deleted,
+        renamed,
+    })
+}
+
+/// The run id to attribute changes to: only runs executing in the project
+/// directory itself, not in an isolated worktree
+fn attributed_run(app: &AppHandle) -> Option<String> {
+    let state = app.state::<AppState>();
+    if state.active_worktree.lock().unwrap().is_some() {
+        return None;
+    }
+    let run_id = state.current_run.lock().unwrap().clone();
+    run_id
+}
+
+/// Keep the fuzzy finder's path index in step with the tree
+fn update_index(app: &AppHandle, root: &Path, event: &FsChangedEvent) {
+    let state = app.state::<AppState>();
+    let mut index = state.file_index.lock().unwrap();
+    if let Some(index) = index.as_mut() {
+        if Path::new(index.root()) == root {
+            index.apply(event);
+        }
+    }
+}
+
+/// Start watching `project_dir`, emitting `fs-changed` events to `app`
+pub(crate) fn start(app: &AppHandle, project_dir: &str) -> Result<ProjectWatcher, String> {
+    let root = PathBuf::from(project_dir);
+    let app = app.clone();
+    let mut matcher = IgnoreMatcher::new(&root);
+    let handler_root = root.clone();
+
+    let mut debouncer = new_debouncer(DEBOUNCE, None, move |result: DebounceEventResult| {
+        let Ok(events) = result else {
+            return;
+        };
+        let mut batch = Batch::default();
+        for event in &events {
+            batch.add(&event.kind, &event.paths);
+        }
+        if let Some(event) = to_event(&handler_root, batch, &mut matcher, attributed_run(&app)) {
+            update_index(&app, &handler_root, &event);
+            let _ = app.emit("fs-changed", event);
+        }
+    })
+    .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+
+    debouncer
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", project_dir, e))?;
+    Ok(debouncer)
+}
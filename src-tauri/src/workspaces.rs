@@ -0,0 +1,452 @@
+//! Monorepo and workspace detection.
+//!
+//! Reads the workspace definitions of npm, Yarn, Bun and pnpm workspaces,
+//! Cargo workspaces, Nx, Turborepo, Lerna, uv and Go (`go.work`, or several
+//! modules side by side), expands their member globs and describes every
+//! member package. Packages are returned as a tree by path, so a Cargo
+//! workspace inside one JavaScript package shows up under that package.
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::{detectors, manifests, walk};
+
+/// How deep to look for member packages
+const MAX_DEPTH: usize = 6;
+
+/// Dependency and build directories that never hold members
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build", ".venv", "venv"];
+
+/// Files that can hold a workspace definition
+const DEFINITION_FILES: &[&str] = &[
+    "package.json",
+    "pnpm-workspace.yaml",
+    "lerna.json",
+    "nx.json",
+    "turbo.json",
+    "Cargo.toml",
+    "pyproject.toml",
+    "go.work",
+];
+
+/// A package, or the project root, and the packages inside it
+#[derive(Clone, Serialize)]
+pub struct WorkspaceNode {
+    pub name: String,
+    /// Relative to the project root; "." for the root
+    pub path: String,
+    /// Absolute path, e.g. for `--add-dir`
+    pub absolute_path: String,
+    /// Workspace tools defined in this directory, e.g. "pnpm", "cargo"
+    pub tools: Vec<String>,
+    pub languages: Vec<String>,
+    pub frameworks: Vec<detectors::Framework>,
+    pub children: Vec<WorkspaceNode>,
+}
+
+/// A workspace definition: which tool, the member patterns and the file
+/// a member must contain
+struct Definition {
+    tool: String,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    marker: &'static str,
+}
+
+/// Directories and marker files under the root, found in one walk
+struct Tree {
+    dirs: Vec<String>,
+    files: BTreeSet<String>,
+}
+
+fn scan(root: &Path) -> Tree {
+    let mut tree = Tree {
+        dirs: Vec::new(),
+        files: BTreeSet::new(),
+    };
+    let walker = walk::walker(root)
+        .max_depth(Some(MAX_DEPTH))
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            name != ".git" && !SKIPPED_DIRS.contains(&name.as_ref())
+        })
+        .build();
+    for entry in walker.flatten().filter(|e| e.depth() > 0) {
+        let relative = walk::relative_path(root, entry.path());
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            tree.dirs.push(relative);
+        } else {
+            tree.files.insert(relative);
+        }
+    }
+    tree.dirs.sort();
+    tree
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn json_strings(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn toml_strings(table: &toml::Table, path: &[&str]) -> Vec<String> {
+    let mut current = Some(table);
+    let (last, parents) = path.split_last().expect("non-empty path");
+    for key in parents {
+        current = current
+            .and_then(|t| t.get(*key))
+            .and_then(toml::Value::as_table);
+    }
+    current
+        .and_then(|t| t.get(*last))
+        .and_then(toml::Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(toml::Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Split `!pattern` exclusions from inclusions
+fn definition(tool: &str, patterns: Vec<String>, marker: &'static str) -> Definition {
+    let (exclude, include): (Vec<String>, Vec<String>) =
+        patterns.into_iter().partition(|p| p.starts_with('!'));
+    Definition {
+        tool: tool.to_string(),
+        include,
+        exclude: exclude
+            .into_iter()
+            .map(|p| p.trim_start_matches('!').to_string())
+            .collect(),
+        marker,
+    }
+}
+
+/// Workspace definitions in `dir`
+fn definitions(dir: &Path) -> Vec<Definition> {
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).ok();
+    let mut found = Vec::new();
+
+    if let Some(package) = read("package.json").and_then(|c| serde_json::from_str::<Value>(&c).ok())
+    {
+        // `"workspaces": [...]` or Yarn's `"workspaces": { "packages": [...] }`
+        let workspaces = package.get("workspaces");
+        let patterns = match workspaces {
+            Some(Value::Object(map)) => json_strings(map.get("packages")),
+            other => json_strings(other),
+        };
+        if !patterns.is_empty() {
+            let tool = manifests::node_package_manager(dir);
+            found.push(definition(tool, patterns, "package.json"));
+        }
+    }
+    if let Some(content) = read("pnpm-workspace.yaml") {
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&content).unwrap_or_default();
+        let patterns: Vec<String> = yaml
+            .get("packages")
+            .and_then(serde_yaml::Value::as_sequence)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(serde_yaml::Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        found.push(definition("pnpm", patterns, "package.json"));
+    }
+    if let Some(lerna) = read("lerna.json").and_then(|c| serde_json::from_str::<Value>(&c).ok()) {
+        let mut patterns = json_strings(lerna.get("packages"));
+        if patterns.is_empty() {
+            patterns.push("packages/*".to_string());
+        }
+        found.push(definition("lerna", patterns, "package.json"));
+    }
+    if dir.join("nx.json").exists() {
+        // Nx projects are any directory with a project.json
+        found.push(definition("nx", vec!["**".to_string()], "project.json"));
+    }
+    if dir.join("turbo.json").exists() {
+        // Turborepo runs over the package manager's workspaces
+        found.push(definition("turborepo", Vec::new(), "package.json"));
+    }
+    if let Some(cargo) = read("Cargo.toml").and_then(|c| toml::from_str::<toml::Table>(&c).ok()) {
+        if cargo.contains_key("workspace") {
+            let mut def = definition(
+                "cargo",
+                toml_strings(&cargo, &["workspace", "members"]),
+                "Cargo.toml",
+            );
+            def.exclude
+                .extend(toml_strings(&cargo, &["workspace", "exclude"]));
+            found.push(def);
+        }
+    }
+    if let Some(pyproject) =
+        read("pyproject.toml").and_then(|c| toml::from_str::<toml::Table>(&c).ok())
+    {
+        let members = toml_strings(&pyproject, &["tool", "uv", "workspace", "members"]);
+        if !members.is_empty() {
+            let mut def = definition("uv", members, "pyproject.toml");
+            def.exclude.extend(toml_strings(
+                &pyproject,
+                &["tool", "uv", "workspace", "exclude"],
+            ));
+            found.push(def);
+        }
+    }
+    if let Some(go_work) = read("go.work") {
+        found.push(definition("go", go_work_uses(&go_work), "go.mod"));
+    }
+    found
+}
+
+/// Module directories from `use` directives in go.work
+fn go_work_uses(content: &str) -> Vec<String> {
+    let mut uses = Vec::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if in_block {
+            if line == ")" {
+                in_block = false;
+            } else if !line.is_empty() {
+                uses.push(line.to_string());
+            }
+        } else if line == "use (" {
+            in_block = true;
+        } else if let Some(dir) = line.strip_prefix("use ") {
+            uses.push(dir.trim().to_string());
+        }
+    }
+    uses
+}
+
+fn glob_set(base: &str, patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern
+            .trim_start_matches("./")
+            .trim_end_matches('/')
+            .to_string();
+        let full = if pattern.is_empty() || pattern == "." {
+            base.to_string()
+        } else {
+            join(base, &pattern)
+        };
+        // `*` stays within one directory, as in every workspace tool
+        if let Ok(glob) = GlobBuilder::new(&full).literal_separator(true).build() {
+            builder.add(glob);
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+/// Member directories of a definition in `base`
+fn members(tree: &Tree, base: &str, def: &Definition) -> Vec<String> {
+    let include = glob_set(base, &def.include);
+    let exclude = glob_set(base, &def.exclude);
+    tree.dirs
+        .iter()
+        .filter(|dir| include.is_match(dir.as_str()) && !exclude.is_match(dir.as_str()))
+        .filter(|dir| tree.files.contains(&join(dir, def.marker)))
+        .cloned()
+        .collect()
+}
+
+fn node(
+    root: &Path,
+    path: &str,
+    tools: Vec<String>,
+    extensions: &detectors::ExtensionCounts,
+) -> WorkspaceNode {
+    let dir = if path.is_empty() {
+        root.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    let found = manifests::read_dir(root, &dir);
+    let stack = detectors::detect(root, &dir, &found, extensions);
+    let name = found
+        .iter()
+        .find_map(|m| m.name.clone())
+        .or_else(|| dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    WorkspaceNode {
+        name,
+        path: if path.is_empty() {
+            ".".to_string()
+        } else {
+            path.to_string()
+        },
+        absolute_path: dir.to_string_lossy().to_string(),
+        tools,
+        languages: stack.language_names(),
+        frameworks: stack.frameworks,
+        children: Vec::new(),
+    }
+}
+
+/// Place `node` under the deepest node whose path contains it
+fn insert(parent: &mut WorkspaceNode, node: WorkspaceNode) {
+    let contains =
+        |outer: &str, inner: &str| outer == "." || inner.starts_with(&format!("{}/", outer));
+    match parent
+        .children
+        .iter_mut()
+        .find(|child| contains(&child.path, &node.path))
+    {
+        Some(child) => insert(child, node),
+        None => parent.children.push(node),
+    }
+}
+
+/// The project's workspace tree, or `None` when it isn't a workspace.
+/// `extensions` are the project's, as counted for the root.
+pub(crate) fn detect(
+    root: &Path,
+    extensions: &detectors::ExtensionCounts,
+) -> Option<WorkspaceNode> {
+    let tree = scan(root);
+    // (directory, tools defined there); directories are relative, "" = root
+    let mut packages: Vec<(String, Vec<String>)> = Vec::new();
+    // Every directory that may define a workspace, the root first; nested
+    // workspaces needn't be members of an outer one
+    let mut pending: Vec<String> = tree
+        .dirs
+        .iter()
+        .rev()
+        .filter(|dir| {
+            DEFINITION_FILES
+                .iter()
+                .any(|name| tree.files.contains(&join(dir, name)))
+        })
+        .cloned()
+        .collect();
+    pending.push(String::new());
+    while let Some(base) = pending.pop() {
+        let dir = if base.is_empty() {
+            root.to_path_buf()
+        } else {
+            root.join(&base)
+        };
+        let defs = definitions(&dir);
+        if defs.is_empty() && !base.is_empty() {
+            continue;
+        }
+        let tools: Vec<String> = defs.iter().map(|d| d.tool.clone()).collect();
+        match packages.iter_mut().find(|(p, _)| *p == base) {
+            Some(entry) => entry.1 = tools,
+            None => packages.push((base.clone(), tools)),
+        }
+        for def in &defs {
+            for member in members(&tree, &base, def) {
+                if packages.iter().all(|(p, _)| *p != member) {
+                    packages.push((member, Vec::new()));
+                }
+            }
+        }
+    }
+
+    // Several Go modules side by side, without a go.work
+    let has_go_work = tree.files.contains("go.work");
+    let go_modules: Vec<String> = tree
+        .files
+        .iter()
+        .filter_map(|f| f.strip_suffix("/go.mod"))
+        .map(str::to_string)
+        .collect();
+    if !has_go_work && go_modules.len() > 1 {
+        packages[0].1.push("go".to_string());
+        for module in go_modules {
+            if packages.iter().all(|(p, _)| *p != module) {
+                packages.push((module, Vec::new()));
+            }
+        }
+    }
+
+    if packages.len() < 2 {
+        return None;
+    }
+    let (_, root_tools) = packages.remove(0);
+    let mut tree_root = node(root, "", root_tools, extensions);
+    packages.sort_by(|a, b| a.0.cmp(&b.0));
+    for (path, tools) in packages {
+        insert(&mut tree_root, node(root, &path, tools, extensions));
+    }
+    Some(tree_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::project;
+
+    fn paths(node: &WorkspaceNode, out: &mut Vec<String>) {
+        out.push(node.path.clone());
+        for child in &node.children {
+            paths(child, out);
+        }
+    }
+
+    #[test]
+    fn detect_expands_globs_and_exclusions() {
+        let root = project(
+            "workspaces-globs",
+            &[
+                (
+                    "package.json",
+                    r#"{"workspaces": ["apps/*", "packages/**", "!packages/legacy"]}"#,
+                ),
+                ("apps/web/package.json", r#"{"name": "web"}"#),
+                ("apps/notes/README.md", ""),
+                ("packages/ui/package.json", r#"{"name": "ui"}"#),
+                ("packages/ui/icons/package.json", r#"{"name": "icons"}"#),
+                ("packages/legacy/package.json", r#"{"name": "legacy"}"#),
+                ("node_modules/dep/package.json", r#"{"name": "dep"}"#),
+            ],
+        );
+        let extensions = detectors::ExtensionCounts::scan(&root);
+        let tree = detect(&root, &extensions).unwrap();
+        let mut found = Vec::new();
+        paths(&tree, &mut found);
+        assert_eq!(
+            found,
+            vec![".", "apps/web", "packages/ui", "packages/ui/icons"]
+        );
+        assert_eq!(tree.tools, vec!["npm"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn detect_needs_more_than_one_package() {
+        let root = project(
+            "workspaces-solo",
+            &[("package.json", r#"{"name": "solo"}"#)],
+        );
+        let extensions = detectors::ExtensionCounts::scan(&root);
+        assert!(detect(&root, &extensions).is_none());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
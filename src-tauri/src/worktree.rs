@@ -0,0 +1,382 @@
+//! Git worktree isolation for sessions.
+//!
+//! An isolated session gets its own `git worktree` on a fresh branch under
+//! the app data directory, and Claude Code runs there instead of in the
+//! user's working copy. The result can then be diffed, merged back or thrown
+//! away. Uncommitted changes in the working copy are not carried over — the
+//! worktree starts from the current `HEAD`.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::{app_data_dir, current_project_dir, git, now_secs, project_key, slugify, AppState};
+
+/// Branch prefix for session branches, so they are easy to spot in `git branch`
+const BRANCH_PREFIX: &str = "cc-desktop/";
+
+/// A session worktree, persisted as JSON next to the worktree itself
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorktreeInfo {
+    pub id: String,
+    pub branch: String,
+    pub path: String,
+    pub base_branch: Option<String>,
+    pub base_commit: String,
+    pub created_at: u64,
+    /// Whether this is the worktree runs currently go to (not persisted)
+    #[serde(default, skip_deserializing)]
+    pub active: bool,
+    /// Files changed relative to the worktree's HEAD (not persisted)
+    #[serde(default, skip_deserializing)]
+    pub changed_files: usize,
+    /// Commits on the session branch since the base (not persisted)
+    #[serde(default, skip_deserializing)]
+    pub commits: usize,
+    /// The worktree directory is gone (e.g. deleted by hand)
+    #[serde(default, skip_deserializing)]
+    pub missing: bool,
+}
+
+/// Diff of a worktree (committed and uncommitted work) against its base
+#[derive(Clone, Serialize)]
+pub struct WorktreeDiff {
+    pub id: String,
+    pub base_commit: String,
+    pub files: Vec<git::FileStat>,
+    pub patch: String,
+}
+
+/// Directory holding all worktrees of one project
+fn worktrees_root(project_dir: &str) -> Result<PathBuf, String> {
+    Ok(app_data_dir()?
+        .join("worktrees")
+        .join(project_key(project_dir)))
+}
+
+/// Metadata file of a worktree. Ids come from the frontend, so only the
+/// characters `create` generates are accepted.
+fn metadata_path(root: &Path, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid worktree id: {}", id));
+    }
+    Ok(root.join(format!("{}.json", id)))
+}
+
+fn save(root: &Path, info: &WorktreeInfo) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(info).map_err(|e| e.to_string())?;
+    std::fs::write(metadata_path(root, &info.id)?, json).map_err(|e| e.to_string())
+}
+
+/// Load a worktree's metadata by id
+pub(crate) fn load(project_dir: &str, id: &str) -> Result<WorktreeInfo, String> {
+    let root = worktrees_root(project_dir)?;
+    let content = std::fs::read_to_string(metadata_path(&root, id)?)
+        .map_err(|_| format!("Worktree not found: {}", id))?;
+    let info: WorktreeInfo = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    if !Path::new(&info.path).is_dir() {
+        return Err(format!("Worktree directory is missing: {}", info.path));
+    }
+    Ok(info)
+}
+
+/// Create a worktree on a new branch from the project's current HEAD
+pub(crate) fn create(project_dir: &str, name: Option<&str>) -> Result<WorktreeInfo, String> {
+    let project = Path::new(project_dir);
+    if !git::is_repo(project) {
+        return Err("Worktree isolation needs a git repository".to_string());
+    }
+    let base_commit = git::run(project, &["rev-parse", "HEAD"])
+        .map_err(|_| "Worktree isolation needs at least one commit".to_string())?;
+    let base_branch = git::run(project, &["symbolic-ref", "--quiet", "--short", "HEAD"]).ok();
+
+    let root = worktrees_root(project_dir)?;
+    std::fs::create_dir_all(&root).map_err(|e| e.to_string())?;
+
+    let base_id = match name.map(slugify) {
+        Some(slug) if !slug.is_empty() => slug,
+        _ => format!("session-{}", now_secs()),
+    };
+
+    // Pick an id whose directory and branch are both free
+    let mut id = base_id.clone();
+    let mut i = 2;
+    loop {
+        let branch_ref = format!("refs/heads/{}{}", BRANCH_PREFIX, id);
+        let branch_taken =
+            git::run(project, &["rev-parse", "--verify", "--quiet", &branch_ref]).is_ok();
+        if !branch_taken && !root.join(&id).exists() && !metadata_path(&root, &id)?.exists() {
+            break;
+        }
+        id = format!("{}-{}", base_id, i);
+        i += 1;
+    }
+
+    let branch = format!("{}{}", BRANCH_PREFIX, id);
+    let path = root.join(&id);
+    let path_str = path.to_string_lossy().to_string();
+    git::run(
+        project,
+        &["worktree", "add", "-b", &branch, &path_str, &base_commit],
+    )?;
+
+    let info = WorktreeInfo {
+        id,
+        branch,
+        path: path_str,
+        base_branch,
+        base_commit,
+        created_at: now_secs(),
+        active: true,
+        changed_files: 0,
+        commits: 0,
+        missing: false,
+    };
+    save(&root, &info)?;
+    Ok(info)
+}
+
+/// Remove a worktree, its branch and its metadata
+fn remove(project_dir: &str, info: &WorktreeInfo) -> Result<(), String> {
+    let project = Path::new(project_dir);
+    if Path::new(&info.path).exists() {
+        git::run(project, &["worktree", "remove", "--force", &info.path])?;
+    } else {
+        // Directory deleted by hand: let git forget about it
+        let _ = git::run(project, &["worktree", "prune"]);
+    }
+    let _ = git::run(project, &["branch", "-D", &info.branch]);
+    let root = worktrees_root(project_dir)?;
+    let _ = std::fs::remove_file(metadata_path(&root, &info.id)?);
+    Ok(())
+}
+
+/// Files git doesn't track yet and doesn't ignore
+fn untracked_files(worktree: &Path) -> Result<Vec<String>, String> {
+    let out = git::run(
+        worktree,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+    Ok(out
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Diff an untracked file against nothing, without touching the index.
+/// `git diff --no-index` exits with 1 when there are differences.
+fn diff_untracked(worktree: &Path, path: &str, args: &[&str]) -> Result<String, String> {
+    let output = git::command(worktree)
+        .args(["diff", "--no-index"])
+        .args(args)
+        .args(["--", "/dev/null", path])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    match output.status.code() {
+        Some(0 | 1) => Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string()),
+        _ => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+    }
+}
+
+/// Append `more` to `text` on a new line
+fn append(text: &mut String, more: &str) {
+    if more.is_empty() {
+        return;
+    }
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text.push_str(more);
+}
+
+/// Create a session worktree, optionally making it the active run target
+#[tauri::command]
+pub fn create_worktree(
+    state: State<AppState>,
+    name: Option<String>,
+    activate: Option<bool>,
+) -> Result<WorktreeInfo, String> {
+    let dir = current_project_dir(&state)?;
+    let mut info = create(&dir, name.as_deref())?;
+    if activate.unwrap_or(true) {
+        *state.active_worktree.lock().unwrap() = Some(info.id.clone());
+    } else {
+        info.active = false;
+    }
+    Ok(info)
+}
+
+/// List the project's session worktrees with their change summary
+#[tauri::command]
+pub fn list_worktrees(state: State<AppState>) -> Result<Vec<WorktreeInfo>, String> {
+    let dir = current_project_dir(&state)?;
+    let active = state.active_worktree.lock().unwrap().clone();
+    let root = worktrees_root(&dir)?;
+
+    let mut worktrees = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&root) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let Ok(mut info) = serde_json::from_str::<WorktreeInfo>(&content) else {
+                continue;
+            };
+            let wt = Path::new(&info.path);
+            info.active = active.as_deref() == Some(info.id.as_str());
+            info.missing = !wt.is_dir();
+            if !info.missing {
+                info.changed_files = git::run(wt, &["status", "--porcelain"])
+                    .map(|out| out.lines().count())
+                    .unwrap_or(0);
+                let range = format!("{}..HEAD", info.base_commit);
+                info.commits = git::run(wt, &["rev-list", "--count", &range])
+                    .ok()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(0);
+            }
+            worktrees.push(info);
+        }
+    }
+
+    worktrees.sort_by_key(|w| std::cmp::Reverse(w.created_at));
+    Ok(worktrees)
+}
+
+/// Choose which worktree runs go to; `None` runs in the project itself
+#[tauri::command]
+pub fn set_active_worktree(state: State<AppState>, id: Option<String>) -> Result<(), String> {
+    let dir = current_project_dir(&state)?;
+    if let Some(id) = &id {
+        load(&dir, id)?;
+    }
+    *state.active_worktree.lock().unwrap() = id;
+    Ok(())
+}
+
+/// Diff a worktree, including uncommitted and untracked files, against its base commit
+#[tauri::command]
+pub fn diff_worktree(state: State<AppState>, id: String) -> Result<WorktreeDiff, String> {
+    let dir = current_project_dir(&state)?;
+    let info = load(&dir, &id)?;
+    let wt = Path::new(&info.path);
+
+    let numstat = git::run(
+        wt,
+        &["diff", "--no-renames", "--numstat", &info.base_commit],
+    )?;
+    let mut files = git::parse_numstat(&numstat);
+    let mut patch = git::run(wt, &["diff", "--no-renames", &info.base_commit])?;
+    for path in untracked_files(wt)? {
+        // The numstat path reads "/dev/null => <path>"
+        let stats = git::parse_numstat(&diff_untracked(wt, &path, &["--numstat"])?);
+        files.extend(stats.into_iter().map(|stat| git::FileStat {
+            path: path.clone(),
+            ..stat
+        }));
+        append(&mut patch, &diff_untracked(wt, &path, &[])?);
+    }
+
+    Ok(WorktreeDiff {
+        id: info.id,
+        base_commit: info.base_commit,
+        files,
+        patch,
+    })
+}
+
+/// Commit pending work in the worktree and merge its branch into the
+/// branch it was created from, which must be checked out in the project.
+/// The worktree is removed once merged. Returns the resulting HEAD commit
+/// of the project.
+#[tauri::command]
+pub fn merge_worktree(
+    state: State<AppState>,
+    id: String,
+    message: Option<String>,
+) -> Result<String, String> {
+    let dir = current_project_dir(&state)?;
+    let info = load(&dir, &id)?;
+    let project = Path::new(&dir);
+    let wt = Path::new(&info.path);
+
+    let Some(base_branch) = &info.base_branch else {
+        return Err(
+            "The worktree was created from a detached HEAD; there is no branch to merge into"
+                .to_string(),
+        );
+    };
+    let current = git::run(project, &["symbolic-ref", "--quiet", "--short", "HEAD"]).ok();
+    if current.as_deref() != Some(base_branch.as_str()) {
+        return Err(format!(
+            "Check out {} before merging; the project is on {}",
+            base_branch,
+            current.as_deref().unwrap_or("a detached HEAD")
+        ));
+    }
+
+    // Commit whatever Claude left uncommitted
+    let pending = git::run(wt, &["status", "--porcelain"])?;
+    if !pending.is_empty() {
+        git::run(wt, &["add", "--all"])?;
+        let message = message
+            .clone()
+            .unwrap_or_else(|| format!("CC Desktop session {}", info.id));
+        let mut cmd = git::command(wt);
+        cmd.args(git::identity_args(wt))
+            .args(["commit", "--no-verify", "-m", &message]);
+        git::output(&mut cmd)?;
+    }
+
+    let merge_message = message.unwrap_or_else(|| format!("Merge {}", info.branch));
+    let mut cmd = git::command(project);
+    cmd.args(git::identity_args(project)).args([
+        "merge",
+        "--no-ff",
+        "-m",
+        &merge_message,
+        &info.branch,
+    ]);
+    if let Err(e) = git::output(&mut cmd) {
+        // Leave the user's working copy as it was
+        let _ = git::run(project, &["merge", "--abort"]);
+        return Err(format!("Merge failed: {}", e));
+    }
+
+    remove(&dir, &info)?;
+    let mut active = state.active_worktree.lock().unwrap();
+    if active.as_deref() == Some(info.id.as_str()) {
+        *active = None;
+    }
+    drop(active);
+
+    git::run(project, &["rev-parse", "HEAD"])
+}
+
+/// Throw away a worktree and its branch without merging
+#[tauri::command]
+pub fn discard_worktree(state: State<AppState>, id: String) -> Result<(), String> {
+    let dir = current_project_dir(&state)?;
+    let root = worktrees_root(&dir)?;
+    let content = std::fs::read_to_string(metadata_path(&root, &id)?)
+        .map_err(|_| format!("Worktree not found: {}", id))?;
+    let info: WorktreeInfo = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    remove(&dir, &info)?;
+
+    let mut active = state.active_worktree.lock().unwrap();
+    if active.as_deref() == Some(id.as_str()) {
+        *active = None;
+    }
+    Ok(())
+}